@@ -16,3 +16,12 @@ echo 'Алексей' | ./сборка/хуяк интер ./примеры/03-
 ./сборка/хуяк интер ./примеры/10-игра-жизнь.хуя           > ./примеры/10-игра-жизнь.хуя.вывод
 ./сборка/хуяк интер ./тесты/тест.хуя                      > ./тесты/тест.хуя.вывод
 
+# Интерпретатор не поддерживает внешние вызовы, поэтому этот тест записываем
+# после компиляции.
+mkdir -p ./сборка/тесты/
+./сборка/хуяк комп -вывод ./сборка/тесты/внешние ./тесты/внешние.хуя
+./сборка/тесты/внешние > ./тесты/внешние.хуя.вывод
+
+for f in ./тесты/ошибки/*.хуя; do
+    HUYA_PATH=./тесты/модули ./сборка/хуяк интер "$f" > "$f.вывод" 2>&1 || true
+done
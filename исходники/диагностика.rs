@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Лок {
@@ -22,6 +23,9 @@ macro_rules! диагностика {
     ($лок:expr, $уровень:literal, $($аргы:tt)*) => {{
         let ::диагностика::Лок{путь_к_файлу, строка, столбец} = $лок;
         let уровень = $уровень;
+        if уровень == "ОШИБКА" {
+            ::диагностика::засчитать_ошибку();
+        }
         eprint!("{путь_к_файлу}:{строка}:{столбец}: {уровень}: ", путь_к_файлу = путь_к_файлу.display());
         eprintln!($($аргы)*);
     }};
@@ -31,9 +35,37 @@ macro_rules! сделать {
     ($лок:expr, $($аргы:tt)*) => {{
         диагностика!($лок, "СДЕЛАТЬ", $($аргы)*);
         диагностика!(&здесь!(), "СДЕЛАТЬ", "реализация находится здесь");
+        ::диагностика::засчитать_ошибку();
     }};
 }
 
+// Ошибки считаются глобально, чтобы разбор и компиляция могли
+// продолжаться после ошибки и при этом не таскать счётчик через все
+// функции. Вся программа однопоточная, так что атомики здесь только ради
+// того, чтобы обойтись без unsafe.
+static КОЛИЧЕСТВО_ОШИБОК: AtomicUsize = AtomicUsize::new(0);
+static МАКС_КОЛИЧЕСТВО_ОШИБОК: AtomicUsize = AtomicUsize::new(МАКС_КОЛИЧЕСТВО_ОШИБОК_ПО_УМОЛЧАНИЮ);
+
+pub const МАКС_КОЛИЧЕСТВО_ОШИБОК_ПО_УМОЛЧАНИЮ: usize = 20;
+
+pub fn засчитать_ошибку() {
+    КОЛИЧЕСТВО_ОШИБОК.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn количество_ошибок() -> usize {
+    КОЛИЧЕСТВО_ОШИБОК.load(Ordering::Relaxed)
+}
+
+pub fn установить_макс_количество_ошибок(количество: usize) {
+    МАКС_КОЛИЧЕСТВО_ОШИБОК.store(количество, Ordering::Relaxed);
+}
+
+/// Пора ли прекращать компиляцию вместо того, чтобы пытаться
+/// восстановиться после очередной ошибки.
+pub fn слишком_много_ошибок() -> bool {
+    количество_ошибок() >= МАКС_КОЛИЧЕСТВО_ОШИБОК.load(Ordering::Relaxed)
+}
+
 pub enum Род {
     Муж,
     Жен,
@@ -128,6 +160,12 @@ pub const ЧИСУЩ_БАЙТ: Чисущ = Чисущ {
     ноль_пять_много: "байт",
 };
 
+pub const ЧИСУЩ_ОШИБКА: Чисущ = Чисущ {
+    один: "ошибка",
+    два_три_четыре: "ошибки",
+    ноль_пять_много: "ошибок",
+};
+
 pub const ЧИСУЩ_ИНСТРУКЦИЙ: Чисущ = Чисущ {
     один: "инструкция",
     два_три_четыре: "инструкции",
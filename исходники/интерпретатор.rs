@@ -3,11 +3,14 @@ use std::io::{Read, Write, BufRead};
 use std::convert::TryInto;
 use super::Результат;
 use std::mem;
+use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::ops::Range;
 use компилятор::{ПП, ВидИнструкции, Инструкция, Имена};
 use типизация::*;
 
 // Разметка памяти
-// |    второй стек    | инициализированные данные | неинициализированные данные |    куча?    |
+// |    второй стек    | инициализированные данные | неинициализированные данные |    куча    |
 // ^                   ^
 // 0                   Начало стека и данных. Стек растет в сторону нуля.
 
@@ -28,6 +31,24 @@ pub struct Машина<'ы> {
     начало_второго_стека: usize,
     pub память: Vec<u8>,
     инструкции: &'ы [Инструкция],
+
+    начало_кучи: usize,
+    /// Выделенные блоки кучи: адрес начала блока и запрошенный размер.
+    выделенные_блоки: BTreeMap<usize, usize>,
+    /// Освобождённые блоки кучи, которые можно переиспользовать: адрес начала и размер.
+    свободные_блоки: Vec<(usize, usize)>,
+
+    /// Сколько инструкций разрешено выполнить. Используется при вычислении
+    /// констант, чтобы бесконечный цикл не подвесил компилятор.
+    pub лимит_инструкций: Option<usize>,
+    /// Запретить ввод-вывод, системные и внешние вызовы. Константы
+    /// вычисляются во время компиляции, и у них не может быть побочных
+    /// эффектов.
+    pub только_чистые_вычисления: bool,
+    /// Смещения глобальных переменных от начала данных. При чистых
+    /// вычислениях обращаться к ним запрещено.
+    pub глобальные_переменные: Vec<Range<usize>>,
+    точка_инициализации: Option<usize>,
 }
 
 macro_rules! ошибка_времени_исполнения {
@@ -61,16 +82,41 @@ impl<'ы> Машина<'ы> {
 
             память: vec![],
             инструкции: &пп.код,
+
+            начало_кучи: 0,
+            выделенные_блоки: BTreeMap::new(),
+            свободные_блоки: Vec::new(),
+
+            лимит_инструкций: None,
+            только_чистые_вычисления: false,
+            глобальные_переменные: Vec::new(),
+            точка_инициализации: пп.точка_инициализации,
         };
 
         // СДЕЛАТЬ: Ресайз вектора капец какой медленный. Возможно из-за
         // инициализации. Надо что-нибудь с этим сделать.
         машина.память.resize(машина.память.len() + объём_второго_стека, 0);
         машина.память.extend_from_slice(пп.иниц_данные.as_slice());
+        for &смещение in &пп.указатели_в_иниц_данных {
+            let ячейка = &mut машина.память[начало_данных + смещение..начало_данных + смещение + РАЗМЕР_СЛОВА];
+            let указатель = u64::from_le_bytes((&*ячейка).try_into().unwrap()) + начало_данных as u64;
+            ячейка.copy_from_slice(&указатель.to_le_bytes());
+        }
         машина.память.resize(машина.память.len() + пп.размер_неиниц_данных, 0);
+        машина.начало_кучи = машина.память.len();
         машина
     }
 
+    /// Перевести адрес в смещение от начала инициализированных данных, если
+    /// он указывает внутрь них.
+    pub fn смещение_в_иниц_данных(&self, адрес: usize, размер_иниц_данных: usize) -> Option<usize> {
+        if self.начало_данных <= адрес && адрес <= self.начало_данных + размер_иниц_данных {
+            Some(адрес - self.начало_данных)
+        } else {
+            None
+        }
+    }
+
     fn протолкнуть_значение_нат(&mut self, значение: usize) -> Результат<()> {
         self.стек.push(значение);
         Ok(())
@@ -95,6 +141,14 @@ impl<'ы> Машина<'ы> {
         Ok(f32::from_le_bytes(self.вытолкнуть_значение_нат()?.to_le_bytes()[0..4].try_into().unwrap()))
     }
 
+    fn протолкнуть_значение_вещ64(&mut self, значение: f64) -> Результат<()> {
+        self.протолкнуть_значение_нат(usize::from_le_bytes(значение.to_le_bytes()))
+    }
+
+    fn вытолкнуть_значение_вещ64(&mut self) -> Результат<f64> {
+        Ok(f64::from_le_bytes(self.вытолкнуть_значение_нат()?.to_le_bytes()))
+    }
+
     // СДЕЛАТЬ: вариант функции срез_памяти, который возвращает массив размера известного на этапе компиляции
     // Возможно через какие-нибудь дженерики. Такой вариант будет очень удобен для чтения примитивных типов
     // из памяти безо всяких этих try_into().unwrap() и прочей лабуды.
@@ -107,6 +161,17 @@ impl<'ы> Машина<'ы> {
             return Err(())
         }
 
+        if адрес >= self.начало_кучи {
+            let блок = self.выделенные_блоки.range(..=адрес).next_back().map(|(&начало, &размер)| (начало, размер));
+            match блок {
+                Some((начало_блока, размер_блока)) if адрес + размер <= начало_блока + размер_блока => {}
+                _ => {
+                    ошибка_времени_исполнения!(self, "Попытка получить доступ к невыделенной или освобождённой памяти кучи [{начало}..{конец})", начало = адрес, конец = адрес+размер);
+                    return Err(())
+                }
+            }
+        }
+
         if let Some(срез) = self.память.get_mut(адрес..адрес+размер) {
             Ok(срез)
         } else {
@@ -115,6 +180,50 @@ impl<'ы> Машина<'ы> {
         }
     }
 
+    fn выделить_в_куче(&mut self, размер: usize) -> Результат<usize> {
+        // Блоки выравниваются по слову, но доступ разрешен только к запрошенному размеру.
+        let Some(размер_блока) = размер.max(1).checked_add(РАЗМЕР_СЛОВА - 1).map(|размер| размер/РАЗМЕР_СЛОВА*РАЗМЕР_СЛОВА) else {
+            ошибка_времени_исполнения!(self, "Не удалось выделить {размер} байт в куче");
+            return Err(())
+        };
+        let адрес = if let Some(индекс) = self.свободные_блоки.iter().position(|&(_, размер_свободного)| размер_свободного >= размер_блока) {
+            let (адрес, размер_свободного) = self.свободные_блоки.remove(индекс);
+            if размер_свободного > размер_блока {
+                self.свободные_блоки.push((адрес + размер_блока, размер_свободного - размер_блока));
+            }
+            self.память[адрес..адрес+размер_блока].fill(0);
+            адрес
+        } else {
+            let адрес = self.память.len();
+            if self.память.try_reserve(размер_блока).is_err() {
+                ошибка_времени_исполнения!(self, "Не удалось выделить {размер} байт в куче");
+                return Err(())
+            }
+            self.память.resize(адрес + размер_блока, 0);
+            адрес
+        };
+        self.выделенные_блоки.insert(адрес, размер);
+        Ok(адрес)
+    }
+
+    fn освободить_в_куче(&mut self, адрес: usize) -> Результат<()> {
+        if адрес == 0 {
+            // Освобождение «пусто» ничего не делает.
+            return Ok(())
+        }
+        if let Some(размер) = self.выделенные_блоки.remove(&адрес) {
+            let размер_блока = (размер.max(1) + РАЗМЕР_СЛОВА - 1)/РАЗМЕР_СЛОВА*РАЗМЕР_СЛОВА;
+            self.свободные_блоки.push((адрес, размер_блока));
+            return Ok(())
+        }
+        if self.свободные_блоки.iter().any(|&(начало, размер)| начало <= адрес && адрес < начало + размер) {
+            ошибка_времени_исполнения!(self, "Повторное освобождение памяти по адресу {адрес}");
+        } else {
+            ошибка_времени_исполнения!(self, "Освобождение памяти по адресу {адрес}, которая не была выделена в куче");
+        }
+        Err(())
+    }
+
     fn количество_элементов_стека(&self) -> usize {
         self.стек.len()
     }
@@ -169,16 +278,52 @@ impl<'ы> Машина<'ы> {
         Ok(значение)
     }
 
+    /// Перед точкой входа выполняется инициализация глобальных переменных,
+    /// если она есть.
     pub fn интерпретировать(&mut self, имена: &Имена, точка_входа: usize, режим_отладки: bool) -> Результат<()> {
+        if let Some(точка_инициализации) = self.точка_инициализации.take() {
+            self.выполнить(имена, точка_инициализации, режим_отладки)?;
+        }
+        self.выполнить(имена, точка_входа, режим_отладки)
+    }
+
+    fn выполнить(&mut self, имена: &Имена, точка_входа: usize, режим_отладки: bool) -> Результат<()> {
         self.индекс_инструкции = точка_входа;
 
         let mut глубина_вызовов = 0;
         let mut цель_перешагивания: Option<usize> = None;
+        let mut выполнено_инструкций = 0;
         self.протолкнуть_значение_нат(self.инструкции.len())?;
         loop {
             let индекс_инструкции = self.индекс_инструкции;
             let инструкция = self.инструкция()?;
 
+            if let Some(лимит) = self.лимит_инструкций {
+                if выполнено_инструкций >= лимит {
+                    диагностика!(&инструкция.лок, "ОШИБКА", "Вычисление не уложилось в {лимит} инструкций. Возможно, оно никогда не завершится.");
+                    return Err(());
+                }
+                выполнено_инструкций += 1;
+            }
+
+            if self.только_чистые_вычисления {
+                match инструкция.вид {
+                    ВидИнструкции::ПечатьСтроки | ВидИнструкции::Ввод | ВидИнструкции::СисВызов{..} => {
+                        диагностика!(&инструкция.лок, "ОШИБКА", "Ввод-вывод невозможен во время компиляции");
+                        return Err(());
+                    }
+                    ВидИнструкции::ВнешнийВызов{..} => {
+                        диагностика!(&инструкция.лок, "ОШИБКА", "Вызов внешних процедур невозможен во время компиляции");
+                        return Err(());
+                    }
+                    ВидИнструкции::ГлобальныеДанные(смещение) if self.глобальные_переменные.iter().any(|переменная| переменная.contains(&(смещение as usize))) => {
+                        диагностика!(&инструкция.лок, "ОШИБКА", "Глобальные переменные недоступны во время компиляции");
+                        return Err(());
+                    }
+                    _ => {}
+                }
+            }
+
             if режим_отладки {
                 if let Some(цель) = цель_перешагивания.clone() {
                     if глубина_вызовов <= цель {
@@ -251,6 +396,57 @@ impl<'ы> Машина<'ы> {
                     self.протолкнуть_значение_нат(значение)?;
                     self.индекс_инструкции += 1;
                 }
+                ВидИнструкции::Обменять => {
+                    self.проверить_арность_аргументов(2)?;
+                    let верхнее = self.вытолкнуть_значение_нат()?;
+                    let нижнее = self.вытолкнуть_значение_нат()?;
+                    self.протолкнуть_значение_нат(верхнее)?;
+                    self.протолкнуть_значение_нат(нижнее)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Прокрутить => {
+                    self.проверить_арность_аргументов(3)?;
+                    let верхнее = self.вытолкнуть_значение_нат()?;
+                    let среднее = self.вытолкнуть_значение_нат()?;
+                    let нижнее = self.вытолкнуть_значение_нат()?;
+                    self.протолкнуть_значение_нат(среднее)?;
+                    self.протолкнуть_значение_нат(верхнее)?;
+                    self.протолкнуть_значение_нат(нижнее)?;
+                    self.индекс_инструкции += 1;
+                }
+                &ВидИнструкции::ПроверитьДиапазон{включительно} => {
+                    self.проверить_арность_аргументов(3)?;
+                    let конец = self.вытолкнуть_значение_нат()?;
+                    let начало = self.вытолкнуть_значение_нат()?;
+                    let количество = self.вытолкнуть_значение_нат()?;
+                    if включительно {
+                        if конец >= количество || начало > конец + 1 {
+                            ошибка_времени_исполнения!(self, "Диапазон [{начало}..{конец}] выходит за границы [0..{количество})");
+                            return Err(());
+                        }
+                        self.протолкнуть_значение_нат(начало)?;
+                        self.протолкнуть_значение_нат(конец + 1)?;
+                    } else {
+                        if начало > конец || конец > количество {
+                            ошибка_времени_исполнения!(self, "Диапазон [{начало}..{конец}) выходит за границы [0..{количество})", начало = начало as i64, конец = конец as i64);
+                            return Err(());
+                        }
+                        self.протолкнуть_значение_нат(начало)?;
+                        self.протолкнуть_значение_нат(конец)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::ПроверитьГраницы => {
+                    self.проверить_арность_аргументов(2)?;
+                    let индекс = self.вытолкнуть_значение_нат()?;
+                    let количество = self.вытолкнуть_значение_нат()?;
+                    if индекс >= количество {
+                        ошибка_времени_исполнения!(self, "Индекс {индекс} выходит за границы [0..{количество})", индекс = индекс as i64);
+                        return Err(());
+                    }
+                    self.протолкнуть_значение_нат(индекс)?;
+                    self.индекс_инструкции += 1;
+                }
                 &ВидИнструкции::ГлобальныеДанные(смещение) => {
                     self.протолкнуть_значение_нат((self.начало_данных as i32 + смещение) as usize)?;
                     self.индекс_инструкции += 1;
@@ -317,8 +513,11 @@ impl<'ы> Машина<'ы> {
                     self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::Записать16 => {
-                    сделать!(&инструкция.лок, "Интерпретация инструкции Записать16");
-                    return Err(());
+                    self.проверить_арность_аргументов(2)?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    let значение = self.вытолкнуть_значение_нат()? as u16;
+                    self.срез_памяти(адрес, 2)?.copy_from_slice(&значение.to_le_bytes());
+                    self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::Записать32 => {
                     self.проверить_арность_аргументов(2)?;
@@ -343,8 +542,11 @@ impl<'ы> Машина<'ы> {
                     self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::ПрочитатьБезЗнак16 => {
-                    сделать!(&инструкция.лок, "Интерпртация инструкции ПрочитатьБезЗнак32");
-                    return Err(());
+                    self.проверить_арность_аргументов(1)?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    let значение: u16 = u16::from_le_bytes(self.срез_памяти(адрес, 2)?.try_into().unwrap());
+                    self.протолкнуть_значение_нат(значение as usize)?;
+                    self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::ПрочитатьБезЗнак32 => {
                     self.проверить_арность_аргументов(1)?;
@@ -354,16 +556,25 @@ impl<'ы> Машина<'ы> {
                     self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::ПрочитатьЗнак8 => {
-                    сделать!(&инструкция.лок, "Интерпртация инструкции ПрочитатьЗнак8");
-                    return Err(());
+                    self.проверить_арность_аргументов(1)?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    let значение: i8 = i8::from_le_bytes(self.срез_памяти(адрес, 1)?.try_into().unwrap());
+                    self.протолкнуть_значение_нат(значение as i64 as usize)?;
+                    self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::ПрочитатьЗнак16 => {
-                    сделать!(&инструкция.лок, "Интерпртация инструкции ПрочитатьЗнак8");
-                    return Err(());
+                    self.проверить_арность_аргументов(1)?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    let значение: i16 = i16::from_le_bytes(self.срез_памяти(адрес, 2)?.try_into().unwrap());
+                    self.протолкнуть_значение_нат(значение as i64 as usize)?;
+                    self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::ПрочитатьЗнак32 => {
-                    сделать!(&инструкция.лок, "Интерпртация инструкции ПрочитатьЗнак32");
-                    return Err(());
+                    self.проверить_арность_аргументов(1)?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    let значение: i32 = i32::from_le_bytes(self.срез_памяти(адрес, 4)?.try_into().unwrap());
+                    self.протолкнуть_значение_нат(значение as i64 as usize)?;
+                    self.индекс_инструкции += 1;
                 }
                 ВидИнструкции::Прочитать64 => {
                     self.проверить_арность_аргументов(1)?;
@@ -377,6 +588,8 @@ impl<'ы> Машина<'ы> {
                     let размер = self.вытолкнуть_значение_нат()?;
                     let цель = self.вытолкнуть_значение_нат()?;
                     let источник = self.вытолкнуть_значение_нат()?;
+                    self.срез_памяти(цель, размер)?;
+                    self.срез_памяти(источник, размер)?;
                     for индекс in 0..размер {
                         self.память[цель + индекс] = self.память[источник + индекс];
                     }
@@ -386,6 +599,8 @@ impl<'ы> Машина<'ы> {
                     let размер = self.вытолкнуть_значение_нат()?;
                     let цель = self.вытолкнуть_значение_нат()?;
                     let источник = self.вытолкнуть_значение_нат()?;
+                    self.срез_памяти(цель, размер)?;
+                    self.срез_памяти(источник, размер)?;
                     let mut равна = true;
                     for индекс in 0..размер {
                         if self.память[цель + индекс] != self.память[источник + индекс] {
@@ -400,6 +615,21 @@ impl<'ы> Машина<'ы> {
                     }
                     self.индекс_инструкции += 1;
                 }
+                ВидИнструкции::СравнитьПамять => {
+                    let размер_второго = self.вытолкнуть_значение_нат()?;
+                    let второй = self.вытолкнуть_значение_нат()?;
+                    let размер_первого = self.вытолкнуть_значение_нат()?;
+                    let первый = self.вытолкнуть_значение_нат()?;
+                    // Пустой срез может и не указывать ни на какую память.
+                    let первые_байты = if размер_первого > 0 { self.срез_памяти(первый, размер_первого)?.to_vec() } else { Vec::new() };
+                    let вторые_байты = if размер_второго > 0 { self.срез_памяти(второй, размер_второго)?.to_vec() } else { Vec::new() };
+                    match первые_байты.cmp(&вторые_байты) {
+                        Ordering::Less => self.протолкнуть_значение_нат(-1i64 as usize)?,
+                        Ordering::Equal => self.протолкнуть_значение_нат(0)?,
+                        Ordering::Greater => self.протолкнуть_значение_нат(1)?,
+                    }
+                    self.индекс_инструкции += 1;
+                }
                 ВидИнструкции::НатМеньше => {
                     self.проверить_арность_аргументов(2)?;
                     let правый = self.вытолкнуть_значение_нат()?;
@@ -633,6 +863,17 @@ impl<'ы> Машина<'ы> {
                     }
                     self.индекс_инструкции += 1;
                 }
+                ВидИнструкции::Вещ32Равно => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ32()?;
+                    let левый = self.вытолкнуть_значение_вещ32()?;
+                    if левый == правый {
+                        self.протолкнуть_значение_нат(1)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
                 ВидИнструкции::Вещ32Больше => {
                     self.проверить_арность_аргументов(2)?;
                     let правый = self.вытолкнуть_значение_вещ32()?;
@@ -660,6 +901,124 @@ impl<'ы> Машина<'ы> {
                     self.протолкнуть_значение_вещ32(-значение)?;
                     self.индекс_инструкции += 1;
                 }
+                ВидИнструкции::КонвертНат64Вещ64 => {
+                    let значение = self.вытолкнуть_значение_нат()?;
+                    self.протолкнуть_значение_вещ64(значение as f64)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::КонвертЦел64Вещ64 => {
+                    let значение = self.вытолкнуть_значение_нат()? as i64;
+                    self.протолкнуть_значение_вещ64(значение as f64)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::КонвертВещ64Нат64 => {
+                    let значение = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_нат(значение as usize)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::КонвертВещ64Цел64 => {
+                    let значение = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_нат((значение as i64) as usize)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::КонвертВещ32Вещ64 => {
+                    let значение = self.вытолкнуть_значение_вещ32()?;
+                    self.протолкнуть_значение_вещ64(значение as f64)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::КонвертВещ64Вещ32 => {
+                    let значение = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_вещ32(значение as f32)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Умножение => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_вещ64(левый * правый)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Деление => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_вещ64(левый / правый)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Сложение => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_вещ64(левый + правый)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Вычитание => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_вещ64(левый - правый)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Меньше => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    if левый < правый {
+                        self.протолкнуть_значение_нат(1)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64МеньшеРавно => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    if левый <= правый {
+                        self.протолкнуть_значение_нат(1)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Равно => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    if левый == правый {
+                        self.протолкнуть_значение_нат(1)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Больше => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    if левый > правый {
+                        self.протолкнуть_значение_нат(1)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64БольшеРавно => {
+                    self.проверить_арность_аргументов(2)?;
+                    let правый = self.вытолкнуть_значение_вещ64()?;
+                    let левый = self.вытолкнуть_значение_вещ64()?;
+                    if левый >= правый {
+                        self.протолкнуть_значение_нат(1)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::Вещ64Отриц => {
+                    let значение = self.вытолкнуть_значение_вещ64()?;
+                    self.протолкнуть_значение_вещ64(-значение)?;
+                    self.индекс_инструкции += 1;
+                }
                 ВидИнструкции::ЛогОтрицание => {
                     self.проверить_арность_аргументов(1)?;
                     let значение = self.вытолкнуть_значение_нат()?;
@@ -733,6 +1092,57 @@ impl<'ы> Машина<'ы> {
                     self.протолкнуть_значение_нат(размер)?;
                     self.индекс_инструкции += 1;
                 }
+                ВидИнструкции::ДекодироватьСимвол => {
+                    self.проверить_арность_аргументов(3)?;
+                    let позиция = self.вытолкнуть_значение_нат()?;
+                    let количество = self.вытолкнуть_значение_нат()?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    // Символ UTF-8 занимает не больше 4 байт.
+                    let доступно = количество.saturating_sub(позиция).clamp(1, 4);
+                    let байты = self.срез_памяти(адрес + позиция, доступно)?;
+                    let корректные = match std::str::from_utf8(байты) {
+                        Ok(текст) => текст,
+                        Err(ошибка) => std::str::from_utf8(&байты[..ошибка.valid_up_to()]).unwrap(),
+                    };
+                    let (символ, длина) = match корректные.chars().next() {
+                        Some(символ) => (символ, символ.len_utf8()),
+                        None => (char::REPLACEMENT_CHARACTER, 1),
+                    };
+                    self.протолкнуть_значение_нат(символ as usize)?;
+                    self.протолкнуть_значение_нат(позиция + длина)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::ЗакодироватьСимвол => {
+                    self.проверить_арность_аргументов(4)?;
+                    let код = self.вытолкнуть_значение_нат()?;
+                    let позиция = self.вытолкнуть_значение_нат()?;
+                    let количество = self.вытолкнуть_значение_нат()?;
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    let символ = char::from_u32(код as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+                    let длина = символ.len_utf8();
+                    if позиция <= количество && длина <= количество - позиция {
+                        символ.encode_utf8(self.срез_памяти(адрес + позиция, длина)?);
+                        self.протолкнуть_значение_нат(длина)?;
+                    } else {
+                        self.протолкнуть_значение_нат(0)?;
+                    }
+                    self.индекс_инструкции += 1;
+                }
+                &ВидИнструкции::ВыделитьПамять{размер_элемента} => {
+                    let количество = self.вытолкнуть_значение_нат()?;
+                    let Some(размер) = количество.checked_mul(размер_элемента) else {
+                        ошибка_времени_исполнения!(self, "Не удалось выделить {количество} элементов по {размер_элемента} байт: размер не помещается в слово");
+                        return Err(())
+                    };
+                    let адрес = self.выделить_в_куче(размер)?;
+                    self.протолкнуть_значение_нат(адрес)?;
+                    self.индекс_инструкции += 1;
+                }
+                ВидИнструкции::ОсвободитьПамять => {
+                    let адрес = self.вытолкнуть_значение_нат()?;
+                    self.освободить_в_куче(адрес)?;
+                    self.индекс_инструкции += 1;
+                }
                 ВидИнструкции::Возврат => {
                     // СДЕЛАТЬ: Ввести отдельную инструкцию останова.
                     // И генерировать точку входа наподобии того, как мы это делаем в эльф.
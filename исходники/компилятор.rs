@@ -1,8 +1,8 @@
 /// Промежуточное Представление
 
 use super::Результат;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use синтаксис::*;
 use диагностика::*;
 use лексика::*;
@@ -20,6 +20,11 @@ pub enum ВидИнструкции {
     Целое(i64),
     Вытолкнуть,
     Продублировать,
+    /// Поменять местами два верхних значения стека аргументов.
+    Обменять,
+    /// Переложить третье сверху значение стека аргументов на вершину:
+    /// [а б в] становится [б в а].
+    Прокрутить,
     /// Протолкнуть указатель на глобальные данные.
     ///
     /// Эта инструкция нужна потому, что мы не знаем во время
@@ -51,6 +56,11 @@ pub enum ВидИнструкции {
     Прочитать64,
     СкопироватьПамять,
     ПамятьРавно,
+    /// Снять со стека адрес и размер в байтах второго участка памяти, затем
+    /// первого, и протолкнуть -1, 0 или 1 как результат лексикографического
+    /// сравнения их байт. Если один участок является началом другого, то
+    /// меньше более короткий.
+    СравнитьПамять,
     НатСложение,
     НатВычитание,
     НатУмножение,
@@ -79,9 +89,26 @@ pub enum ВидИнструкции {
     Вещ32Деление,
     Вещ32Меньше,
     Вещ32МеньшеРавно,
+    Вещ32Равно,
     Вещ32Больше,
     Вещ32БольшеРавно,
     Вещ32Отриц,
+    КонвертНат64Вещ64,
+    КонвертЦел64Вещ64,
+    КонвертВещ64Нат64,
+    КонвертВещ64Цел64,
+    КонвертВещ32Вещ64,
+    КонвертВещ64Вещ32,
+    Вещ64Сложение,
+    Вещ64Вычитание,
+    Вещ64Умножение,
+    Вещ64Деление,
+    Вещ64Меньше,
+    Вещ64МеньшеРавно,
+    Вещ64Равно,
+    Вещ64Больше,
+    Вещ64БольшеРавно,
+    Вещ64Отриц,
     ЛогОтрицание,
     БитИли,
     БитИ,
@@ -90,9 +117,38 @@ pub enum ВидИнструкции {
     БитСмещениеВправо,
     ПечатьСтроки,
     Ввод,
+    /// Снять со стека индекс и количество элементов, и протолкнуть индекс
+    /// обратно. Если индекс выходит за границы, то программа завершается с
+    /// ошибкой.
+    ПроверитьГраницы,
+    /// Снять со стека конец и начало диапазона и количество элементов, и
+    /// протолкнуть начало и конец (не включительно) обратно. Конец
+    /// включительно, если `включительно`, иначе нет. Если диапазон пуст не
+    /// с той стороны или выходит за границы, то программа завершается с
+    /// ошибкой.
+    ПроверитьДиапазон{ включительно: bool },
+    /// Снять со стека позицию, количество байт и их адрес. Декодировать
+    /// символ UTF-8, начинающийся с байта по позиции, и протолкнуть символ
+    /// и позицию за ним. Некорректная или обрезанная последовательность
+    /// даёт символ замены U+FFFD и занимает один байт.
+    ДекодироватьСимвол,
+    /// Снять со стека символ, позицию, количество байт и их адрес.
+    /// Закодировать символ в UTF-8 начиная с байта по позиции и протолкнуть
+    /// количество записанных байт, либо 0, если символ не поместился.
+    /// Суррогаты и значения больше 10FFFF кодируются как U+FFFD.
+    ЗакодироватьСимвол,
+    /// Снять со стека количество элементов по `размер_элемента` байт и
+    /// протолкнуть адрес выделенной под них в куче памяти. Если память
+    /// выделить не удалось, в том числе из-за того, что её размер не
+    /// помещается в слово, то программа завершается с ошибкой.
+    ВыделитьПамять{ размер_элемента: usize },
+    /// Снять со стека адрес памяти, выделенной инструкцией ВыделитьПамять, и вернуть её в кучу.
+    ОсвободитьПамять,
     Возврат,
     ВнутреннийВызов(usize),
-    ВнешнийВызов{индекс: usize, арность: usize, результат: Option<Тип>},
+    /// Параметры нужны, чтобы разложить аргументы по регистрам: целые и
+    /// указатели идут в общие регистры, вещественные в xmm.
+    ВнешнийВызов{индекс: usize, параметры: Vec<Тип>, результат: Option<Тип>},
     // СДЕЛАТЬ: инструкции изменения потока исполнения должны принимать относительное смещение.
     // Данный подход позволяет создавать легко перемещаемый код. Хотя, я не уверен, что это подойдет
     // для ВызватьВнутренююПроцедуру.
@@ -115,6 +171,14 @@ pub struct СкомпПеременная {
     pub имя: Лексема,
     pub тип: Тип,
     pub смещение: i32,
+    /// По смещению лежит не сама переменная, а её адрес.
+    pub ссылка: bool,
+    /// Глобальная переменная лежит в инициализированных данных, а не в
+    /// неинициализированных.
+    pub в_иниц_данных: bool,
+    /// Элемент цикла «для … в …» по строковому литералу или константе.
+    /// Он ссылается на неизменяемые данные, так что его можно только читать.
+    pub только_чтение: bool,
 }
 
 #[derive(Default, Clone)]
@@ -124,12 +188,52 @@ pub struct ОбластьВидимости {
     pub размер_переменных_на_стеке: usize,
 }
 
+/// Цикл, внутри которого компилируются утверждения. Нужен для «прервать»
+/// и «продолжить», чьи прыжки заплатываются после компиляции всего цикла.
+#[derive(Clone)]
+pub struct СкомпЦикл {
+    pub метка: Option<Лексема>,
+    /// Количество областей видимости до области тела цикла. Все области,
+    /// начиная с этой, освобождаются при выходе из цикла.
+    pub глубина_областей: usize,
+    pub прыжки_на_выход: Vec<usize>,
+    pub прыжки_на_продолжение: Vec<usize>,
+}
+
 #[derive(Default, Clone)]
 pub struct ЛокальныеИмена {
     pub стек_областей: Vec<ОбластьВидимости>,
+    pub стек_циклов: Vec<СкомпЦикл>,
 }
 
 impl ЛокальныеИмена {
+    fn начать_цикл(&mut self, метка: &Option<Лексема>) -> Результат<()> {
+        if let Some(метка) = метка {
+            if let Some(внешняя_метка) = self.стек_циклов.iter().find_map(|цикл| цикл.метка.as_ref().filter(|внешняя_метка| внешняя_метка.текст == метка.текст)) {
+                диагностика!(&метка.лок, "ОШИБКА", "Метка «{имя}» уже используется объемлющим циклом", имя = метка.текст);
+                диагностика!(&внешняя_метка.лок, "ИНФО", "Объемлющий цикл находится здесь");
+                return Err(());
+            }
+        }
+        self.стек_циклов.push(СкомпЦикл {
+            метка: метка.clone(),
+            глубина_областей: self.стек_областей.len(),
+            прыжки_на_выход: Vec::new(),
+            прыжки_на_продолжение: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn закончить_цикл(&mut self, пп: &mut ПП, точка_продолжения: usize, точка_выхода: usize) {
+        let цикл = self.стек_циклов.pop().expect("Каждый начатый цикл заканчивается ровно один раз");
+        for прыжок in цикл.прыжки_на_продолжение {
+            пп.код[прыжок].вид = ВидИнструкции::Прыжок(точка_продолжения);
+        }
+        for прыжок in цикл.прыжки_на_выход {
+            пп.код[прыжок].вид = ВидИнструкции::Прыжок(точка_выхода);
+        }
+    }
+
     fn найти_переменную_по_имени(&self, имя: &str) -> Option<&СкомпПеременная> {
         self.стек_областей.iter().rev().find_map(|область| область.переменные.get(имя))
     }
@@ -145,6 +249,12 @@ pub enum ТочкаВхода {
     Внешняя { индекс: usize },
 }
 
+/// Адрес внутренней процедуры, тело которой ещё не скомпилировано.
+const АДРЕС_НЕИЗВЕСТЕН: usize = usize::MAX;
+
+/// Сколько инструкций разрешено выполнить при вычислении одной константы.
+const ЛИМИТ_ИНСТРУКЦИЙ_КОНСТАНТЫ: usize = 10_000_000;
+
 #[derive(Clone, Debug)]
 pub struct РезультатСкомпПроцедуры {
     тип: Тип,
@@ -157,6 +267,26 @@ pub struct СкомпПроцедура {
     pub параметры: Vec<СкомпПеременная>,
     pub результат: Option<РезультатСкомпПроцедуры>,
     pub точка_входа: ТочкаВхода,
+    /// Модуль, в котором определена процедура. None для основного файла.
+    pub модуль: Option<String>,
+    /// Процедура, помеченная «скрыт», видна только внутри своего модуля.
+    pub скрытая: bool,
+}
+
+impl СкомпПроцедура {
+    fn ключ(&self) -> String {
+        ключ_процедуры(&self.модуль, self.скрытая, &self.имя.текст)
+    }
+}
+
+/// Ключ процедуры в Имена::процедуры. Скрытые процедуры модуля хранятся под
+/// полным именем «модуль.имя», поэтому они не конфликтуют с именами из
+/// других модулей и основного файла.
+fn ключ_процедуры(модуль: &Option<String>, скрытая: bool, имя: &str) -> String {
+    match модуль {
+        Some(модуль) if скрытая => format!("{модуль}.{имя}"),
+        _ => имя.to_string(),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -164,6 +294,13 @@ pub struct СкомпКонстанта {
     pub имя: Лексема,
     pub тип: Тип,
     pub значение_или_адрес: u64,
+    /// «значение_или_адрес» является смещением от начала инициализированных
+    /// данных. Так хранятся константы непримитивных типов и указатели внутрь
+    /// этих данных.
+    pub в_иниц_данных: bool,
+    /// Модуль, в котором определена константа. None для основного файла
+    /// и локальных констант.
+    pub модуль: Option<String>,
 }
 
 /// Промежуточное Представление
@@ -173,9 +310,23 @@ pub struct ПП {
     pub иниц_данные: Vec<u8>,
     pub размер_неиниц_данных: usize,
     pub заплатки_неиниц_указателей: Vec<usize>,
+    /// Смещения в иниц_данные, по которым лежат 64-битные указатели на
+    /// сами инициализированные данные, записанные как смещение от их
+    /// начала. При загрузке программы к ним прибавляется адрес начала данных.
+    pub указатели_в_иниц_данных: Vec<usize>,
+    /// Инструкции ВнутреннийВызов и имена вызываемых процедур. Адрес
+    /// процедуры известен только после компиляции её тела, которое может
+    /// идти после вызова.
+    pub заплатки_вызовов: Vec<(usize, String)>,
     pub строки: HashMap<String, usize>,
     pub внешние_символы: HashMap<String, usize>,
     pub библиотеки: HashMap<String, usize>,
+    /// Не генерировать инструкции ПроверитьГраницы при индексировании
+    /// массивов и срезов.
+    pub без_проверок_границ: bool,
+    /// Код, который присваивает глобальным переменным начальные значения,
+    /// не известные во время компиляции. Выполняется перед точкой входа.
+    pub точка_инициализации: Option<usize>,
 }
 
 impl ПП {
@@ -240,16 +391,39 @@ pub struct Имена {
     pub процедуры: HashMap<String, СкомпПроцедура>,
     pub переменные: HashMap<String, СкомпПеременная>,
     pub структуры: HashMap<String, Структура>,
+    pub перечисления: HashMap<String, Перечисление>,
+    /// Имена всех включённых модулей.
+    pub модули: HashSet<String>,
 }
 
 impl Имена {
+    /// Найти процедуру по имени, как оно записано в коде: «имя» либо
+    /// «модуль.имя». Внутри модуля его скрытые процедуры перекрывают
+    /// одноимённые процедуры снаружи.
+    fn найти_процедуру(&self, имя: &str, текущий_модуль: &Option<String>) -> Option<&СкомпПроцедура> {
+        if let Some((модуль, имя)) = имя.split_once('.') {
+            if текущий_модуль.as_deref() == Some(модуль) {
+                if let Some(процедура) = self.процедуры.get(&ключ_процедуры(текущий_модуль, true, имя)) {
+                    return Some(процедура);
+                }
+            }
+            return self.процедуры.get(имя).filter(|процедура| процедура.модуль.as_deref() == Some(модуль));
+        }
+        if текущий_модуль.is_some() {
+            if let Some(процедура) = self.процедуры.get(&ключ_процедуры(текущий_модуль, true, имя)) {
+                return Some(процедура);
+            }
+        }
+        self.процедуры.get(имя)
+    }
+
     fn верифицировать_переопределение_имени(&self, имя: &Лексема) -> Результат<()> {
         if let Some(существующая_переменная) = self.переменные.get(&имя.текст) {
             диагностика!(&имя.лок, "ОШИБКА",
                          "уже существует переменная с именем «{имя}»",
                          имя = имя.текст);
             диагностика!(&существующая_переменная.имя.лок, "ИНФО",
-                         "она определена здесь здесь. Выберите другое имя.");
+                         "она определена здесь. Выберите другое имя.");
             return Err(())
         }
 
@@ -258,7 +432,7 @@ impl Имена {
                          "уже существует процедура с именем «{имя}»",
                          имя = имя.текст);
             диагностика!(&существующая_процедура.имя.лок, "ИНФО",
-                         "она определена здесь здесь. Выберите другое имя.");
+                         "она определена здесь. Выберите другое имя.");
             return Err(())
         }
 
@@ -267,18 +441,97 @@ impl Имена {
                          "уже существует константа с именем «{имя}»",
                          имя = имя.текст);
             диагностика!(&существующая_константа.имя.лок, "ИНФО",
-                         "она определена здесь здесь. Выберите другое имя.");
+                         "она определена здесь. Выберите другое имя.");
+            return Err(())
+        }
+
+        Ok(())
+    }
+
+    /// Скрытая процедура конфликтует только с другими скрытыми процедурами
+    /// своего модуля.
+    fn верифицировать_переопределение_скрытой_процедуры(&self, имя: &Лексема, ключ: &str) -> Результат<()> {
+        if let Some(существующая_процедура) = self.процедуры.get(ключ) {
+            диагностика!(&имя.лок, "ОШИБКА",
+                         "уже существует процедура с именем «{имя}»",
+                         имя = имя.текст);
+            диагностика!(&существующая_процедура.имя.лок, "ИНФО",
+                         "она определена здесь. Выберите другое имя.");
             return Err(())
         }
+        Ok(())
+    }
 
+    fn верифицировать_переопределение_типа(&self, имя: &Лексема) -> Результат<()> {
+        if let Some(дубль) = self.структуры.get(&имя.текст) {
+            диагностика!(&имя.лок, "ОШИБКА", "Структура «{имя}» уже существует", имя = имя.текст);
+            диагностика!(&дубль.имя.лок, "ИНФО", "Она определена здесь");
+            return Err(());
+        }
+        if let Some(дубль) = self.перечисления.get(&имя.текст) {
+            диагностика!(&имя.лок, "ОШИБКА", "Перечисление «{имя}» уже существует", имя = имя.текст);
+            диагностика!(&дубль.имя.лок, "ИНФО", "Оно определено здесь");
+            return Err(());
+        }
         Ok(())
     }
 }
 
+const КЛЮЧИ_ОПРЕДЕЛЕНИЙ: &[ВидЛексемы] = &[
+    ВидЛексемы::КлючПер,
+    ВидЛексемы::КлючПро,
+    ВидЛексемы::КлючСкрыт,
+    ВидЛексемы::КлючКонст,
+    ВидЛексемы::КлючВкл,
+    ВидЛексемы::КлючБибл,
+    ВидЛексемы::КлючСтрукт,
+    ВидЛексемы::КлючПеречисление,
+    ВидЛексемы::Конец,
+];
+
+/// Ключевые слова определений, которые также встречаются внутри процедур.
+const КЛЮЧИ_ЛОКАЛЬНЫХ_ОПРЕДЕЛЕНИЙ: &[ВидЛексемы] = &[
+    ВидЛексемы::КлючПер,
+    ВидЛексемы::КлючКонст,
+];
+
+/// Пропустить лексемы до начала следующего определения верхнего уровня.
+/// Остальные ключевые слова определений встречаются только на верхнем
+/// уровне, а «пер» и «конст» бывают и внутри процедур. Их считаем началом
+/// определения, только если они не правее неудачного определения, которое
+/// начиналось в столбце `столбец_определения`.
+fn пропустить_определение(лекс: &mut Лексер, столбец_определения: usize) {
+    loop {
+        match лекс.подсмотреть_лексему() {
+            Ok(лексема) => {
+                if лексема.вид == ВидЛексемы::Конец {
+                    return;
+                }
+                if КЛЮЧИ_ОПРЕДЕЛЕНИЙ.contains(&лексема.вид) {
+                    if !КЛЮЧИ_ЛОКАЛЬНЫХ_ОПРЕДЕЛЕНИЙ.contains(&лексема.вид) || лексема.лок.столбец <= столбец_определения {
+                        return;
+                    }
+                }
+            }
+            Err(()) => {}
+        }
+        let _ = лекс.вытащить_лексему();
+    }
+}
+
+const КАТАЛОГИ_МОДУЛЕЙ_ПО_УМОЛЧАНИЮ: &[&str] = &[".", "./модули"];
+
 #[derive(Default)]
 pub struct Программа {
     pub пп: ПП,
     pub имена: Имена,
+    /// Дополнительные каталоги поиска модулей. Просматриваются раньше
+    /// каталогов по умолчанию.
+    pub пути_поиска_модулей: Vec<PathBuf>,
+    /// Уже включённые модули. Повторное включение ничего не делает.
+    включённые_модули: HashSet<PathBuf>,
+    /// Модули, которые разбираются прямо сейчас, и где они были включены.
+    стек_включений: Vec<(PathBuf, Лексема)>,
 }
 
 fn скомпилировать_утверждение_вызова_функции_по_имени(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &mut ЛокальныеИмена, имя: &Лексема, аргументы: &[Выражение]) -> Результат<()> {
@@ -288,18 +541,31 @@ fn скомпилировать_утверждение_вызова_функци
             let mut временный_размер = 0;
             for арг in аргументы {
                 let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &арг, &mut временный_размер)?;
+                проверить_литерал_по_умолчанию(&арг, &тип)?;
                 match тип {
                     Тип::Нат64 => {
                         let процедура = достать_и_проверить_процедуру_печати(имена, "печать_нат", &тип, &арг.лок())?;
-                        вызвать_процедуру(арг.лок().clone(), имена, процедура, &mut пп.код, &mut временный_размер)?;
+                        вызвать_процедуру(арг.лок().clone(), имена, процедура, пп, &mut временный_размер)?;
                     }
                     Тип::Цел64 => {
                         let процедура = достать_и_проверить_процедуру_печати(имена, "печать_цел", &тип, &арг.лок())?;
-                        вызвать_процедуру(арг.лок().clone(), имена, процедура, &mut пп.код, &mut временный_размер)?;
+                        вызвать_процедуру(арг.лок().clone(), имена, процедура, пп, &mut временный_размер)?;
                     }
                     Тип::Лог => {
                         let процедура = достать_и_проверить_процедуру_печати(имена, "печать_лог", &тип, &арг.лок())?;
-                        вызвать_процедуру(арг.лок().clone(), имена, процедура, &mut пп.код, &mut временный_размер)?;
+                        вызвать_процедуру(арг.лок().clone(), имена, процедура, пп, &mut временный_размер)?;
+                    }
+                    Тип::Вещ32 => {
+                        let процедура = достать_и_проверить_процедуру_печати(имена, "печать_вещ32", &тип, &арг.лок())?;
+                        вызвать_процедуру(арг.лок().clone(), имена, процедура, пп, &mut временный_размер)?;
+                    }
+                    Тип::Вещ64 => {
+                        let процедура = достать_и_проверить_процедуру_печати(имена, "печать_вещ64", &тип, &арг.лок())?;
+                        вызвать_процедуру(арг.лок().clone(), имена, процедура, пп, &mut временный_размер)?;
+                    }
+                    Тип::Символ => {
+                        let процедура = достать_и_проверить_процедуру_печати(имена, "печать_символа", &тип, &арг.лок())?;
+                        вызвать_процедуру(арг.лок().clone(), имена, процедура, пп, &mut временный_размер)?;
                     }
                     Тип::Срез{тип_элемента} => {
                         if *тип_элемента != Тип::Нат8 {
@@ -320,8 +586,40 @@ fn скомпилировать_утверждение_вызова_функци
             }
             Ok(())
         },
+        "освободить" => {
+            let [выражение] = аргументы else {
+                let количество_аргументов = аргументы.len();
+                let количество_параметров = 1;
+                диагностика!(&имя.лок, "ОШИБКА",
+                             "Неверное количество аргументов вызова процедуры. Процедура принимает {количество_параметров} {параметров}, но в данном вызове предоставлено лишь {количество_аргументов} {аргументов}.",
+                             параметров = ЧИСУЩ_ПАРАМЕТР.текст(количество_параметров),
+                             аргументов = ЧИСУЩ_АРГУМЕНТ.текст(количество_аргументов));
+                return Err(());
+            };
+
+            let mut временный_размер = 0;
+            let тип_выражения = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &выражение, &mut временный_размер)?;
+            match тип_выражения {
+                Тип::Указатель{..} => {}
+                Тип::Срез{..} => {
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: имя.лок.clone()});
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone()});
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone()});
+                }
+                _ => {
+                    диагностика!(&выражение.лок(), "ОШИБКА", "Несоответствие типов данных. Ожидался тип «указатель», либо «срез», но повстречался тип «{действительный}»",
+                                 действительный = тип_выражения.текст());
+                    return Err(());
+                }
+            }
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьПамять, лок: имя.лок.clone()});
+            if временный_размер > 0 {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: имя.лок.clone() });
+            }
+            Ok(())
+        }
         _ => {
-            if let Some(вызываемая_процедура) = имена.процедуры.get(&имя.текст) {
+            if let Some(вызываемая_процедура) = имена.найти_процедуру(&имя.текст, &текущая_процедура.модуль) {
                 if let Some(результат) = &вызываемая_процедура.результат {
                     диагностика!(&имя.лок, "ОШИБКА", "Данная процедура возвращает результат типа «{тип}». Результаты процедур нельзя игнорировать.", тип = результат.тип.текст());
                     диагностика!(&вызываемая_процедура.имя.лок, "ИНФО", "Процедура определена тут.");
@@ -340,22 +638,35 @@ fn скомпилировать_утверждение_вызова_функци
 
                 let mut временный_размер = 0;
                 for (параметр, аргумент) in вызываемая_процедура.параметры.iter().zip(аргументы.iter()).rev() {
-                    let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, аргумент, &mut временный_размер)?;
-                    проверить_типы(&аргумент.лок(), &параметр.тип, &тип)?;
+                    скомпилировать_аргумент(пп, имена, текущая_процедура, локальные_имена, параметр, аргумент, &mut временный_размер)?;
                 }
-                вызвать_процедуру(имя.лок.clone(), имена, вызываемая_процедура, &mut пп.код, &mut временный_размер)?;
+                вызвать_процедуру(имя.лок.clone(), имена, вызываемая_процедура, пп, &mut временный_размер)?;
                 if временный_размер > 0 {
                     пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: имя.лок.clone() });
                 }
                 Ok(())
             } else {
                 диагностика!(&имя.лок, "ОШИБКА", "Неизвестная процедура «{имя}»", имя = имя.текст);
+                сообщить_о_скрытой_процедуре(имена, имя);
                 Err(())
             }
         }
     }
 }
 
+/// Скрытые процедуры хранятся под полным именем «модуль.имя», так что
+/// снаружи модуля найти_процедура их не видит ни по короткому, ни по полному
+/// имени. Подсказываем, где они определены.
+fn сообщить_о_скрытой_процедуре(имена: &Имена, имя: &Лексема) {
+    let mut скрытые: Vec<&СкомпПроцедура> = имена.процедуры.values()
+        .filter(|процедура| процедура.скрытая && (процедура.имя.текст == имя.текст || процедура.ключ() == имя.текст))
+        .collect();
+    скрытые.sort_by(|а, б| а.модуль.cmp(&б.модуль));
+    for процедура in скрытые {
+        диагностика!(&процедура.имя.лок, "ИНФО", "Процедура «{имя}» скрыта и доступна только внутри своего модуля.", имя = процедура.имя.текст);
+    }
+}
+
 fn скомпилировать_выражение_вызова_функции_по_имени(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, имя: &Лексема, аргументы: &[Выражение], временный_размер: &mut usize) -> Результат<Тип> {
     match имя.текст.as_str() {
         // СДЕЛАТЬ: не позволять переопределять процедуру интринсиков в пользовательском коде.
@@ -418,19 +729,19 @@ fn скомпилировать_выражение_вызова_функции_
             };
 
             if let Выражение::Идент(имя) = массив {
-                if let Some((переменная, локация)) = найти_переменную_по_имени(&имя.текст, имена, текущая_процедура, локальные_имена) {
-                    match &переменная.тип {
+                if let Some((тип, локация, смещение)) = найти_адресуемое_имя(&имя.текст, имена, текущая_процедура, локальные_имена) {
+                    match &тип {
                         Тип::Массив{размер: _, тип_элемента} => {
                             let тип_среза = Тип::Срез {
                                 тип_элемента: тип_элемента.clone()
                             };
 
                             let тип_размера_среза = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &размер_среза, временный_размер)?;
-                            проверить_типы(&размер_среза.лок(), &Тип::Нат64, &тип_размера_среза)?;
-                            локация.протолкнуть_смещение(пп, &имя.лок, переменная.смещение);
+                            проверить_тип_значения(пп, &размер_среза, &Тип::Нат64, тип_размера_среза)?;
+                            локация.протолкнуть_смещение(пп, &имя.лок, смещение);
 
                             let тип_начала = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &начало_среза, временный_размер)?;
-                            проверить_типы(&размер_среза.лок(), &Тип::Нат64, &тип_начала)?;
+                            проверить_тип_значения(пп, &начало_среза, &Тип::Нат64, тип_начала)?;
 
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_элемента.размер(&имена.структуры)), лок: имя.лок.clone() });
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: имя.лок.clone() });
@@ -453,15 +764,15 @@ fn скомпилировать_выражение_вызова_функции_
                             };
 
                             let тип_размера_среза = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &размер_среза, временный_размер)?;
-                            проверить_типы(&размер_среза.лок(), &Тип::Нат64, &тип_размера_среза)?;
+                            проверить_тип_значения(пп, &размер_среза, &Тип::Нат64, тип_размера_среза)?;
 
-                            локация.протолкнуть_смещение(пп, &имя.лок, переменная.смещение);
+                            локация.протолкнуть_смещение(пп, &имя.лок, смещение);
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: имя.лок.clone()});
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone()});
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone()});
 
                             let тип_начала = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &начало_среза, временный_размер)?;
-                            проверить_типы(&размер_среза.лок(), &Тип::Нат64, &тип_начала)?;
+                            проверить_тип_значения(пп, &начало_среза, &Тип::Нат64, тип_начала)?;
 
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_элемента.размер(&имена.структуры)), лок: имя.лок.clone() });
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: имя.лок.clone() });
@@ -507,7 +818,7 @@ fn скомпилировать_выражение_вызова_функции_
 
             for аргумент in аргументы {
                 let тип_аргумента = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, аргумент, временный_размер)?;
-                проверить_типы(&аргумент.лок(), &Тип::Нат64, &тип_аргумента)?;
+                проверить_тип_значения(пп, аргумент, &Тип::Нат64, тип_аргумента)?;
             }
 
             пп.код.push(Инструкция{ вид: ВидИнструкции::СисВызов{арность}, лок: имя.лок.clone() });
@@ -539,6 +850,46 @@ fn скомпилировать_выражение_вызова_функции_
                 }
             }
         }
+        "выделить" => {
+            let (тип_элемента, количество) = match аргументы {
+                [тип_элемента] => (тип_элемента, None),
+                [тип_элемента, количество] => (тип_элемента, Some(количество)),
+                _ => {
+                    let количество_аргументов = аргументы.len();
+                    диагностика!(&имя.лок, "ОШИБКА",
+                                 "Неверное количество аргументов вызова процедуры. Процедура принимает 1 или 2 параметра, но в данном вызове предоставлено {количество_аргументов} {аргументов}.",
+                                 аргументов = ЧИСУЩ_АРГУМЕНТ.текст(количество_аргументов));
+                    return Err(());
+                }
+            };
+            let тип_элемента = скомпилировать_тип(пп, тип_элемента, имена, Some(локальные_имена))?;
+            let размер_элемента = тип_элемента.размер(&имена.структуры);
+
+            if let Some(количество) = количество {
+                // выделить(тип, количество) возвращает срез
+                let тип_количества = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, количество, временный_размер)?;
+                проверить_тип_значения(пп, количество, &Тип::Нат64, тип_количества)?;
+                let тип_среза = Тип::Срез{тип_элемента: Box::new(тип_элемента)};
+
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: имя.лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьПамять{размер_элемента}, лок: имя.лок.clone() });
+
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(тип_среза.размер(&имена.структуры) as u64), лок: имя.лок.clone() });
+                *временный_размер += тип_среза.размер(&имена.структуры);
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ВершинаСтека(СРЕЗ_АДРЕС_СМЕЩЕНИЕ as i32), лок: имя.лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: имя.лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ВершинаСтека(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ as i32), лок: имя.лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: имя.лок.clone() });
+
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ВершинаСтека(0), лок: имя.лок.clone() });
+                Ok(тип_среза)
+            } else {
+                // выделить(тип) возвращает указатель на один элемент
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(1), лок: имя.лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьПамять{размер_элемента}, лок: имя.лок.clone() });
+                Ok(Тип::Указатель{тип_элемента: Box::new(тип_элемента)})
+            }
+        }
         "адрес" => {
             let [выражение] = аргументы else {
                 let количество_аргументов = аргументы.len();
@@ -565,8 +916,84 @@ fn скомпилировать_выражение_вызова_функции_
                 }
             }
         }
+        "прочитать_символ" => {
+            let [строка, позиция] = аргументы else {
+                let количество_аргументов = аргументы.len();
+                let количество_параметров = 2;
+                диагностика!(&имя.лок, "ОШИБКА",
+                             "Неверное количество аргументов вызова процедуры. Процедура принимает {количество_параметров} {параметров}, но в данном вызове предоставлено лишь {количество_аргументов} {аргументов}.",
+                             параметров = ЧИСУЩ_ПАРАМЕТР.текст(количество_параметров),
+                             аргументов = ЧИСУЩ_АРГУМЕНТ.текст(количество_аргументов));
+                return Err(());
+            };
+            if !является_левым_выражением(позиция) {
+                диагностика!(позиция.лок(), "ОШИБКА", "Позиция передаётся по ссылке и сдвигается за прочитанный символ. Аргументом может быть только переменная, её поле или элемент.");
+                return Err(());
+            }
+
+            let тип_строки = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, строка, временный_размер)?;
+            проверить_тип_значения(пп, строка, &Тип::Срез{тип_элемента: Box::new(Тип::Нат8)}, тип_строки)?;
+            let тип_позиции = скомпилировать_левое_выражение(пп, имена, текущая_процедура, локальные_имена, позиция, временный_размер)?;
+            проверить_тип_значения(пп, позиция, &Тип::Нат64, тип_позиции)?;
+
+            // На стеке: адрес строки, адрес позиции. Раскладываем их в адрес
+            // позиции, адрес байт строки, их количество и саму позицию.
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ), лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone() });
+            if !пп.без_проверок_границ {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: позиция.лок().clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: позиция.лок().clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: позиция.лок().clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ПроверитьГраницы, лок: позиция.лок().clone() });
+            }
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ДекодироватьСимвол, лок: имя.лок.clone() });
+
+            // На стеке: адрес позиции, символ, новая позиция.
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: имя.лок.clone() });
+            Ok(Тип::Символ)
+        }
+        "записать_символ" => {
+            let [буфер, позиция, символ] = аргументы else {
+                let количество_аргументов = аргументы.len();
+                let количество_параметров = 3;
+                диагностика!(&имя.лок, "ОШИБКА",
+                             "Неверное количество аргументов вызова процедуры. Процедура принимает {количество_параметров} {параметров}, но в данном вызове предоставлено лишь {количество_аргументов} {аргументов}.",
+                             параметров = ЧИСУЩ_ПАРАМЕТР.текст(количество_параметров),
+                             аргументов = ЧИСУЩ_АРГУМЕНТ.текст(количество_аргументов));
+                return Err(());
+            };
+
+            let тип_буфера = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, буфер, временный_размер)?;
+            проверить_тип_значения(пп, буфер, &Тип::Срез{тип_элемента: Box::new(Тип::Нат8)}, тип_буфера)?;
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ), лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone() });
+
+            let тип_позиции = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, позиция, временный_размер)?;
+            проверить_тип_значения(пп, позиция, &Тип::Нат64, тип_позиции)?;
+            let тип_символа = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, символ, временный_размер)?;
+            проверить_тип_значения(пп, символ, &Тип::Символ, тип_символа)?;
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ЗакодироватьСимвол, лок: имя.лок.clone() });
+            Ok(Тип::Нат64)
+        }
         _ => {
-            if let Some(процедура) = имена.процедуры.get(&имя.текст) {
+            if let Some(процедура) = имена.найти_процедуру(&имя.текст, &текущая_процедура.модуль) {
                 // СДЕЛАТЬ: дупликация кода между компиляцией вызова процедур как выражений и как утверждений.
 
                 let количество_аргументов = аргументы.len();
@@ -580,10 +1007,9 @@ fn скомпилировать_выражение_вызова_функции_
                 }
 
                 for (параметр, аргумент) in процедура.параметры.iter().zip(аргументы.iter()).rev() {
-                    let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, аргумент, временный_размер)?;
-                    проверить_типы(&аргумент.лок(), &параметр.тип, &тип)?;
+                    скомпилировать_аргумент(пп, имена, текущая_процедура, локальные_имена, параметр, аргумент, временный_размер)?;
                 }
-                вызвать_процедуру(имя.лок.clone(), имена, &процедура, &mut пп.код, временный_размер)?;
+                вызвать_процедуру(имя.лок.clone(), имена, &процедура, пп, временный_размер)?;
 
                 if let Some(результат) = &процедура.результат {
                     return Ok(результат.тип.clone())
@@ -593,16 +1019,20 @@ fn скомпилировать_выражение_вызова_функции_
                 }
             }
 
-            if let Some((переменная, локация)) = найти_переменную_по_имени(&имя.текст, имена, текущая_процедура, локальные_имена) {
-                match &переменная.тип {
-                    Тип::Массив{тип_элемента, ..} => {
+            if let Some((тип, локация, смещение)) = найти_адресуемое_имя(&имя.текст, имена, текущая_процедура, локальные_имена) {
+                if let [диапазон @ Выражение::Диапазон{..}] = аргументы {
+                    локация.протолкнуть_смещение(пп, &имя.лок, смещение);
+                    return скомпилировать_срез_диапазоном(пп, имена, текущая_процедура, локальные_имена, &тип, диапазон, временный_размер);
+                }
+                match &тип {
+                    Тип::Массив{тип_элемента, размер} => {
                         let [индекс] = &аргументы[..] else {
                             диагностика!(&имя.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.len());
                             return Err(());
                         };
 
-                        локация.протолкнуть_смещение(пп, &имя.лок, переменная.смещение);
-                        индексировать_массив(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
+                        локация.протолкнуть_смещение(пп, &имя.лок, смещение);
+                        индексировать_массив(тип_элемента, *размер, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
                         if тип_элемента.примитивный() {
                             прочитать_примитивный_тип(&имя.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
                         }
@@ -613,29 +1043,31 @@ fn скомпилировать_выражение_вызова_функции_
                             диагностика!(&имя.лок, "ОШИБКА", "Некорректная размерность среза. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.len());
                             return Err(());
                         };
-                        локация.протолкнуть_смещение(пп, &имя.лок, переменная.смещение + СРЕЗ_АДРЕС_СМЕЩЕНИЕ as i32);
-                        прочитать_примитивный_тип(&имя.лок, &mut пп.код, &имена.структуры, &Тип::Нат64)?;
-                        индексировать_массив(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
+                        локация.протолкнуть_смещение(пп, &имя.лок, смещение);
+                        индексировать_срез(тип_элемента, &имя.лок, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
                         if тип_элемента.примитивный() {
                             прочитать_примитивный_тип(&имя.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
                         }
                         return Ok(*тип_элемента.clone())
                     }
                     _ => {
-                        диагностика!(&имя.лок, "ОШИБКА", "Невозможно индексировать тип «{тип}»", тип = переменная.тип.текст());
+                        диагностика!(&имя.лок, "ОШИБКА", "Невозможно индексировать тип «{тип}»", тип = тип.текст());
                         return Err(())
                     }
                 }
             }
 
-            println!("{текущая_процедура:?}");
             диагностика!(&имя.лок, "ОШИБКА", "Неизвестное имя «{имя}»", имя = имя.текст);
+            сообщить_о_скрытой_процедуре(имена, имя);
             Err(())
         }
     }
 }
 
 fn скомпилировать_выражение(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, выражение: &Выражение, временный_размер: &mut usize) -> Результат<Тип> {
+    if let Some(выражение) = разрешить_имя_модуля(имена, текущая_процедура, локальные_имена, выражение) {
+        return скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &выражение, временный_размер);
+    }
     match выражение {
         Выражение::Лог(лексема, значение) => {
             if *значение {
@@ -654,10 +1086,23 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
             Ok(Тип::Нат64)
         },
         Выражение::ВещЧисло(лексема, число) => {
-            let число = u32::from_le_bytes(число.to_le_bytes()) as usize;
+            let число = u32::from_le_bytes((*число as f32).to_le_bytes()) as usize;
             пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(число), лок: лексема.лок.clone() });
             Ok(Тип::Вещ32)
         },
+        Выражение::Вещ64Число(лексема, число) => {
+            let число = u64::from_le_bytes(число.to_le_bytes()) as usize;
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(число), лок: лексема.лок.clone() });
+            Ok(Тип::Вещ64)
+        },
+        Выражение::Символ(лексема, знак) => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(*знак as usize), лок: лексема.лок.clone() });
+            if знак.is_ascii() {
+                Ok(Тип::Нат8)
+            } else {
+                Ok(Тип::Символ)
+            }
+        },
         Выражение::Строка(строка) => {
             let (указатель, длинна) = if let Some(указатель) = пп.строки.get(&строка.текст) {
                 (*указатель, строка.текст.len())
@@ -690,7 +1135,11 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
         }
         Выражение::Идент(лексема) => {
             if let Some(константа) = найти_константу_по_имени(&лексема.текст, имена, Some(локальные_имена)) {
-                пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(константа.значение_или_адрес as usize), лок: лексема.лок.clone() });
+                if константа.в_иниц_данных {
+                    Локация::ИницДанные.протолкнуть_смещение(пп, &лексема.лок, константа.значение_или_адрес as i32);
+                } else {
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(константа.значение_или_адрес as usize), лок: лексема.лок.clone() });
+                }
                 return Ok(константа.тип.clone());
             }
             if let Some((переменная, локация)) = найти_переменную_по_имени(&лексема.текст, имена, текущая_процедура, локальные_имена) {
@@ -709,6 +1158,10 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
         Выражение::Биноп {ключ, вид, левое, правое} => {
             match вид {
                 ВидБинопа::Поле => {
+                    if let Some((тип, значение)) = значение_перечисления(имена, левое, правое)? {
+                        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(значение), лок: ключ.лок.clone() });
+                        return Ok(тип);
+                    }
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     match &**правое {
                         Выражение::Идент(имя_поля) => {
@@ -737,7 +1190,7 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                             }
                         }
                         Выражение::ЦепочкаВызовов{имя: имя_поля, цепочка_аргументов} => {
-                            let mut тип_вызываемого = match &левый_тип {
+                            let тип_вызываемого = match &левый_тип {
                                 Тип::Структура(имя_структуры) => {
                                     let структура = имена
                                         .структуры
@@ -761,45 +1214,7 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                                 }
                             };
 
-                            for аргументы in цепочка_аргументов {
-                                match &тип_вызываемого {
-                                    Тип::Массив{тип_элемента, ..} => {
-                                        let [индекс] = &аргументы.выражения[..] else {
-                                            диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                                            return Err(());
-                                        };
-
-                                        индексировать_массив(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                                        if тип_элемента.примитивный() {
-                                            прочитать_примитивный_тип(&аргументы.ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
-                                        }
-                                        тип_вызываемого = *тип_элемента.clone();
-                                    }
-                                    Тип::Срез{тип_элемента} => {
-
-                                        let [индекс] = &аргументы.выражения[..] else {
-                                            диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                                            return Err(());
-                                        };
-
-                                        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: аргументы.ключ.лок.clone()});
-                                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: аргументы.ключ.лок.clone()});
-                                        пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: аргументы.ключ.лок.clone()});
-
-                                        индексировать_массив(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                                        if тип_элемента.примитивный() {
-                                            прочитать_примитивный_тип(&аргументы.ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
-                                        }
-                                        тип_вызываемого = *тип_элемента.clone();
-                                    }
-                                    _ => {
-                                        диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Невозможно индексировать тип «{тип}»", тип = тип_вызываемого.текст());
-                                        return Err(())
-                                    }
-                                }
-                            }
-
-                            Ok(тип_вызываемого)
+                            скомпилировать_цепочку_индексов(пп, имена, текущая_процедура, локальные_имена, тип_вызываемого, цепочка_аргументов, временный_размер)
                         }
                         _ => {
                             диагностика!(&правое.лок(), "ОШИБКА", "Данное выражение не может быть использовано как поле");
@@ -809,10 +1224,16 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 }
                 ВидБинопа::НеРавно => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if let Тип::Срез{..} = левый_тип {
+                        скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, &левый_тип, правое, временный_размер, false, ВидИнструкции::НатРавно)?;
+                        пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ.лок.clone() });
+                        return Ok(Тип::Лог);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
-                    if левый_тип.примитивный() {
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ключ.лок.clone() });
+                    if let Some(сравнение) = инструкция_сравнения(&левый_тип, &ВидБинопа::Равно) {
+                        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
                     } else {
                         пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(левый_тип.размер(&имена.структуры)), лок: ключ.лок.clone() });
                         пп.код.push(Инструкция{ вид: ВидИнструкции::ПамятьРавно, лок: ключ.лок.clone() });
@@ -822,10 +1243,14 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 }
                 ВидБинопа::Равно => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if let Тип::Срез{..} = левый_тип {
+                        return скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, &левый_тип, правое, временный_размер, false, ВидИнструкции::НатРавно);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
-                    if левый_тип.примитивный() {
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ключ.лок.clone() });
+                    if let Some(сравнение) = инструкция_сравнения(&левый_тип, &ВидБинопа::Равно) {
+                        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
                     } else {
                         пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(левый_тип.размер(&имена.структуры)), лок: ключ.лок.clone() });
                         пп.код.push(Инструкция{ вид: ВидИнструкции::ПамятьРавно, лок: ключ.лок.clone() });
@@ -834,99 +1259,84 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 }
                 ВидБинопа::МеньшеРавно => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if let Тип::Срез{..} | Тип::Массив{..} = левый_тип {
+                        return скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, &левый_тип, правое, временный_размер, true, ВидИнструкции::ЦелМеньшеРавно);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
-                    match левый_тип {
-                        Тип::Нат64 | Тип::Нат8 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::НатМеньшеРавно, лок: ключ.лок.clone() });
-                        }
-                        Тип::Цел64 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелМеньшеРавно, лок: ключ.лок.clone() });
-                        }
-                        Тип::Вещ32 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32МеньшеРавно, лок: ключ.лок.clone() });
-                        }
-                        _ => {
-                            диагностика!(&ключ.лок, "ОШИБКА", "Меньше либо равно для типов «{тип}» не поддерживается",
-                                         тип = левый_тип.текст());
-                            return Err(());
-                        }
+                    if let Some(сравнение) = инструкция_сравнения(&левый_тип, &ВидБинопа::МеньшеРавно) {
+                        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
+                    } else {
+                        диагностика!(&ключ.лок, "ОШИБКА", "Меньше либо равно для типов «{тип}» не поддерживается",
+                                     тип = левый_тип.текст());
+                        return Err(());
                     }
                     Ok(Тип::Лог)
                 }
                 ВидБинопа::БольшеРавно => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if let Тип::Срез{..} | Тип::Массив{..} = левый_тип {
+                        return скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, &левый_тип, правое, временный_размер, true, ВидИнструкции::ЦелБольшеРавно);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
-                    match левый_тип {
-                        Тип::Нат64 | Тип::Нат8 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::НатБольшеРавно, лок: ключ.лок.clone() });
-                        }
-                        Тип::Цел64 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелБольшеРавно, лок: ключ.лок.clone() });
-                        }
-                        Тип::Вещ32 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32БольшеРавно, лок: ключ.лок.clone() });
-                        }
-                        _ => {
-                            диагностика!(&ключ.лок, "ОШИБКА", "Больше либо равно для типов «{тип}» не поддерживается",
-                                         тип = левый_тип.текст());
-                            return Err(());
-                        }
+                    if let Some(сравнение) = инструкция_сравнения(&левый_тип, &ВидБинопа::БольшеРавно) {
+                        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
+                    } else {
+                        диагностика!(&ключ.лок, "ОШИБКА", "Больше либо равно для типов «{тип}» не поддерживается",
+                                     тип = левый_тип.текст());
+                        return Err(());
                     }
                     Ok(Тип::Лог)
                 }
                 ВидБинопа::Меньше => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if let Тип::Срез{..} | Тип::Массив{..} = левый_тип {
+                        return скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, &левый_тип, правое, временный_размер, true, ВидИнструкции::ЦелМеньше);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
-                    match левый_тип {
-                        Тип::Нат64 | Тип::Нат8 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::НатМеньше, лок: ключ.лок.clone() });
-                        }
-                        Тип::Цел64 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелМеньше, лок: ключ.лок.clone() });
-                        }
-                        Тип::Вещ32 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Меньше, лок: ключ.лок.clone() });
-                        }
-                        _ => {
-                            // СДЕЛАТЬ: Поддержка бинопа «меньше» для всех нужных типов
-                            диагностика!(&ключ.лок, "ОШИБКА", "Меньше для типов «{тип}» не поддерживается",
-                                         тип = левый_тип.текст());
-                            return Err(());
-                        }
+                    if let Some(сравнение) = инструкция_сравнения(&левый_тип, &ВидБинопа::Меньше) {
+                        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
+                    } else {
+                        // СДЕЛАТЬ: Поддержка бинопа «меньше» для всех нужных типов
+                        диагностика!(&ключ.лок, "ОШИБКА", "Меньше для типов «{тип}» не поддерживается",
+                                     тип = левый_тип.текст());
+                        return Err(());
                     }
                     Ok(Тип::Лог)
                 }
                 ВидБинопа::Больше => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if let Тип::Срез{..} | Тип::Массив{..} = левый_тип {
+                        return скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, &левый_тип, правое, временный_размер, true, ВидИнструкции::ЦелБольше);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
-                    match левый_тип {
-                        Тип::Цел64 | Тип::Нат8 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелБольше, лок: ключ.лок.clone() });
-                        }
-                        Тип::Нат64 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::НатБольше, лок: ключ.лок.clone() });
-                        }
-                        Тип::Вещ32 => {
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Больше, лок: ключ.лок.clone() });
-                        }
-                        _ => {
-                            диагностика!(&ключ.лок, "ОШИБКА", "Больше для типов «{тип}» не поддерживается",
-                                         тип = левый_тип.текст());
-                            return Err(());
-                        }
+                    if let Some(сравнение) = инструкция_сравнения(&левый_тип, &ВидБинопа::Больше) {
+                        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
+                    } else {
+                        диагностика!(&ключ.лок, "ОШИБКА", "Больше для типов «{тип}» не поддерживается",
+                                     тип = левый_тип.текст());
+                        return Err(());
                     }
                     Ok(Тип::Лог)
                 }
                 ВидБинопа::Или => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if левый_тип == Тип::Лог {
+                        // Если левое истинно, то правое не вычисляем.
+                        return скомпилировать_сокращённую_логику(пп, имена, текущая_процедура, локальные_имена, ключ, правое, false);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Лог | Тип::Нат64 | Тип::Нат8 | Тип::Цел64 => {
+                        Тип::Лог | Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитИли, лок: ключ.лок.clone() });
                         }
                         _ => {
@@ -938,10 +1348,15 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 }
                 ВидБинопа::И => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
+                    if левый_тип == Тип::Лог {
+                        // Если левое ложно, то правое не вычисляем.
+                        return скомпилировать_сокращённую_логику(пп, имена, текущая_процедура, локальные_имена, ключ, правое, true);
+                    }
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Лог |Тип::Нат64 | Тип::Нат8 | Тип::Цел64 => {
+                        Тип::Лог | Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: ключ.лок.clone() });
                         }
                         _ => {
@@ -954,9 +1369,10 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::Либо => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Лог | Тип::Нат64 | Тип::Нат8 | Тип::Цел64 => {
+                        Тип::Лог | Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитЛибо, лок: ключ.лок.clone() });
                         }
                         _ => {
@@ -969,14 +1385,24 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::Сложение => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    if let Тип::Указатель{тип_элемента} = &левый_тип {
+                        масштабировать_смещение_указателя(пп, имена, правое, &правый_тип, тип_элемента)?;
+                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: ключ.лок.clone() });
+                        return Ok(левый_тип);
+                    }
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Нат8 | Тип::Нат64 | Тип::Цел64 => {
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
                         Тип::Вещ32 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Сложение, лок: ключ.лок.clone() });
                         }
+                        Тип::Вещ64 => {
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ64Сложение, лок: ключ.лок.clone() });
+                        }
                         _ => {
                             диагностика!(&ключ.лок, "ОШИБКА", "Сложение для типов «{тип}» не поддерживается",
                                          тип = левый_тип.текст());
@@ -988,14 +1414,31 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::Вычитание => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    if let Тип::Указатель{тип_элемента} = &левый_тип {
+                        if левый_тип == правый_тип {
+                            // Разность указателей измеряется в элементах
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::НатВычитание, лок: ключ.лок.clone() });
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Целое(тип_элемента.размер(&имена.структуры) as i64), лок: ключ.лок.clone() });
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелДеление, лок: ключ.лок.clone() });
+                            return Ok(Тип::Цел64);
+                        }
+                        масштабировать_смещение_указателя(пп, имена, правое, &правый_тип, тип_элемента)?;
+                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатВычитание, лок: ключ.лок.clone() });
+                        return Ok(левый_тип);
+                    }
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Нат8 | Тип::Нат64 | Тип::Цел64 => {
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатВычитание, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
                         Тип::Вещ32 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Вычитание, лок: ключ.лок.clone() });
                         }
+                        Тип::Вещ64 => {
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ64Вычитание, лок: ключ.лок.clone() });
+                        }
                         _ => {
                             диагностика!(&ключ.лок, "ОШИБКА", "Вычитание для типов «{тип}» не поддерживается",
                                          тип = левый_тип.текст());
@@ -1007,17 +1450,23 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::Умножение => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Цел64 => {
+                        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелУмножение, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
-                        Тип::Нат8 | Тип::Нат64 => {
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
                         Тип::Вещ32 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Умножение, лок: ключ.лок.clone() });
                         }
+                        Тип::Вещ64 => {
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ64Умножение, лок: ключ.лок.clone() });
+                        }
                         _ => {
                             диагностика!(&ключ.лок, "ОШИБКА", "Перемножение для типов «{тип}» не поддерживается",
                                          тип = левый_тип.текст());
@@ -1029,17 +1478,22 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::Деление => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Цел64 => {
+                        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелДеление, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
-                        Тип::Нат8 | Тип::Нат64 => {
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::НатДеление, лок: ключ.лок.clone() });
                         }
                         Тип::Вещ32 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Деление, лок: ключ.лок.clone() });
                         }
+                        Тип::Вещ64 => {
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ64Деление, лок: ключ.лок.clone() });
+                        }
                         _ => {
                             диагностика!(&ключ.лок, "ОШИБКА", "Деление для типов «{тип}» не поддерживается",
                                          тип = левый_тип.текст());
@@ -1051,11 +1505,12 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::Остаток => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
+                    let (левый_тип, правый_тип) = уточнить_типы_операндов(пп, &левое, левый_тип, &правое, правый_тип)?;
                     проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
                     match левый_тип {
-                        Тип::Цел64 => пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелОстаток, лок: ключ.лок.clone() }),
-                        Тип::Нат8 | Тип::Нат64 => пп.код.push(Инструкция{ вид: ВидИнструкции::НатОстаток, лок: ключ.лок.clone() }),
-                        Тип::Вещ32 => {
+                        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелОстаток, лок: ключ.лок.clone() }),
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => пп.код.push(Инструкция{ вид: ВидИнструкции::НатОстаток, лок: ключ.лок.clone() }),
+                        Тип::Вещ32 | Тип::Вещ64 => {
                             сделать!(&ключ.лок, "Остаток от деления вещественных чисел. Что-то типа fmodf, только встроенный.");
                             return Err(());
                         }
@@ -1078,121 +1533,166 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                     }
 
                     match &тип_значения {
-                        Тип::Нат8 => match &тип_цели {
-                            Тип::Нат8 | Тип::Нат64 | Тип::Цел64 => {},
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+                        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => match &тип_цели {
+                            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+                            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
+                                if !целый_тип_вмещает(&тип_цели, &тип_значения) {
+                                    привести_целое(пп, &ключ.лок, &тип_цели);
+                                }
+                            }
+                            Тип::Символ => {
+                                if !целый_тип_вмещает(&Тип::Нат32, &тип_значения) {
+                                    привести_целое(пп, &ключ.лок, &Тип::Нат32);
+                                }
+                            }
+                            Тип::Указатель{..} if тип_значения == Тип::Нат64 => {},
                             Тип::Лог => {
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0), лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ.лок.clone() });
                             }
                             Тип::Вещ32 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертНат64Вещ32, лок: ключ.лок.clone() });
+                                if тип_значения.примитивное_знаковое_чтение() == Some(true) {
+                                    пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертЦел64Вещ32, лок: ключ.лок.clone() });
+                                } else {
+                                    пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертНат64Вещ32, лок: ключ.лок.clone() });
+                                }
+                            }
+                            Тип::Вещ64 => {
+                                if тип_значения.примитивное_знаковое_чтение() == Some(true) {
+                                    пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертЦел64Вещ64, лок: ключ.лок.clone() });
+                                } else {
+                                    пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертНат64Вещ64, лок: ключ.лок.clone() });
+                                }
                             }
                             _ => {
                                 диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
                                 return Err(());
                             }
                         }
-                        Тип::Нат64 => match &тип_цели {
-                            Тип::Нат64 | Тип::Цел64 => {},
-                            Тип::Нат8 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0xFF), лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: ключ.лок.clone() });
+                        Тип::Вещ32 => match &тип_цели {
+                            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Нат64, лок: ключ.лок.clone() });
+                                привести_целое(пп, &ключ.лок, &тип_цели);
+                            }
+                            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Цел64, лок: ключ.лок.clone() });
+                                привести_целое(пп, &ключ.лок, &тип_цели);
                             }
                             Тип::Лог => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Нат64, лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0), лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ.лок.clone() });
                             }
-                            Тип::Вещ32 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертНат64Вещ32, лок: ключ.лок.clone() });
+                            Тип::Вещ32 => {}
+                            Тип::Вещ64 => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Вещ64, лок: ключ.лок.clone() });
                             }
                             _ => {
                                 диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
                                 return Err(());
                             }
                         }
-                        Тип::Цел64 => match &тип_цели {
-                            Тип::Нат64 | Тип::Цел64 => {},
-                            Тип::Нат8 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0xFF), лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: ключ.лок.clone() });
+                        Тип::Вещ64 => match &тип_цели {
+                            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ64Нат64, лок: ключ.лок.clone() });
+                                привести_целое(пп, &ключ.лок, &тип_цели);
+                            }
+                            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ64Цел64, лок: ключ.лок.clone() });
+                                привести_целое(пп, &ключ.лок, &тип_цели);
                             }
                             Тип::Лог => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ64Нат64, лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0), лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ключ.лок.clone() });
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ.лок.clone() });
                             }
                             Тип::Вещ32 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертЦел64Вещ32, лок: ключ.лок.clone() });
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ64Вещ32, лок: ключ.лок.clone() });
                             }
                             _ => {
                                 диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
                                 return Err(());
                             }
                         }
-                        Тип::Вещ32 => match &тип_цели {
-                            Тип::Нат64 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Нат64, лок: ключ.лок.clone() });
-                            }
-                            Тип::Цел64 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Цел64, лок: ключ.лок.clone() });
-                            }
-                            Тип::Нат8 => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Нат64, лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0xFF), лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: ключ.лок.clone() });
-                            }
-                            Тип::Лог => {
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертВещ32Нат64, лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0), лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ключ.лок.clone() });
-                                пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ.лок.clone() });
+                        Тип::Символ => match &тип_цели {
+                            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+                            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
+                                if !целый_тип_вмещает(&тип_цели, &Тип::Нат32) {
+                                    привести_целое(пп, &ключ.лок, &тип_цели);
+                                }
                             }
-                            Тип::Вещ32 => {}
                             _ => {
                                 диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
                                 return Err(());
                             }
                         }
                         Тип::Лог => match &тип_цели {
-                            Тип::Нат8 | Тип::Нат64 | Тип::Цел64 | Тип::Лог => {},
+                            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+                            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 | Тип::Лог => {},
                             Тип::Вещ32 => {
                                 пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертНат64Вещ32, лок: ключ.лок.clone() });
                             }
+                            Тип::Вещ64 => {
+                                пп.код.push(Инструкция{ вид: ВидИнструкции::КонвертНат64Вещ64, лок: ключ.лок.clone() });
+                            }
                             _ => {
                                 диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
                                 return Err(());
                             }
                         }
-                        Тип::Срез{..} => match &тип_цели {
-                            Тип::Срез{..} => {}
+                        Тип::Указатель{..} => match &тип_цели {
+                            Тип::Нат64 | Тип::Указатель{..} => {},
                             _ => {
-                                диагностика!(&ключ.лок, "ОШИБКА", "Срезы можно конвертировать только в другие срезы.");
+                                диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
                                 return Err(());
                             }
                         }
-                        _ => {
-                            диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
-                            return Err(());
+                        Тип::Пусто => match &тип_цели {
+                            Тип::Нат64 | Тип::Указатель{..} => {},
+                            _ => {
+                                диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
+                                return Err(());
+                            }
                         }
-                    }
-                    Ok(тип_цели)
-                }
+                        Тип::Перечисление(_) => match &тип_цели {
+                            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+                            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => привести_целое(пп, &ключ.лок, &тип_цели),
+                            _ => {
+                                диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
+                                return Err(());
+                            }
+                        }
+                        Тип::Срез{..} => match &тип_цели {
+                            Тип::Срез{..} => {}
+                            _ => {
+                                диагностика!(&ключ.лок, "ОШИБКА", "Срезы можно конвертировать только в другие срезы.");
+                                return Err(());
+                            }
+                        }
+                        _ => {
+                            диагностика!(&ключ.лок, "ОШИБКА", "Конвертация типа «{тип_значения}» в тип «{тип_цели}» невозможна", тип_значения = тип_значения.текст(), тип_цели = тип_цели.текст());
+                            return Err(());
+                        }
+                    }
+                    Ok(тип_цели)
+                }
                 ВидБинопа::ЛевоеБитовоеСмещение => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
-                    проверить_типы(правое.лок(), &Тип::Нат64, &правый_тип)?;
+                    проверить_тип_значения(пп, &правое, &Тип::Нат64, правый_тип)?;
                     match левый_тип {
                         Тип::Нат64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитСмещениеВлево, лок: ключ.лок.clone() });
                         }
-                        Тип::Нат8 => {
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитСмещениеВлево, лок: ключ.лок.clone() });
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0xFF), лок: ключ.лок.clone() });
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
-                        Тип::Цел64 => {
+                        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             сделать!(левое.лок(), "Знаковое битовое смещение пока не поддерживается");
                             return Err(())
                         }
@@ -1206,17 +1706,16 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                 ВидБинопа::ПравоеБитовоеСмещение => {
                     let левый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &левое, временный_размер)?;
                     let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &правое, временный_размер)?;
-                    проверить_типы(правое.лок(), &Тип::Нат64, &правый_тип)?;
+                    проверить_тип_значения(пп, &правое, &Тип::Нат64, правый_тип)?;
                     match левый_тип {
                         Тип::Нат64 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитСмещениеВправо, лок: ключ.лок.clone() });
                         }
-                        Тип::Нат8 => {
+                        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 => {
                             пп.код.push(Инструкция{ вид: ВидИнструкции::БитСмещениеВправо, лок: ключ.лок.clone() });
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0xFF), лок: ключ.лок.clone() });
-                            пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: ключ.лок.clone() });
+                            привести_целое(пп, &ключ.лок, &левый_тип);
                         }
-                        Тип::Цел64 => {
+                        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                             сделать!(левое.лок(), "Знаковое битовое смещение пока не поддерживается");
                             return Err(())
                         }
@@ -1230,6 +1729,25 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
             }
         }
 
+        Выражение::Пусто(лексема) => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0), лок: лексема.лок.clone() });
+            Ok(Тип::Пусто)
+        }
+        Выражение::Адрес{ключ, выражение} => {
+            if !является_левым_выражением(выражение) {
+                диагностика!(&ключ.лок, "ОШИБКА", "Взять адрес можно только у переменной, её поля или элемента");
+                return Err(());
+            }
+            let тип = скомпилировать_левое_выражение(пп, имена, текущая_процедура, локальные_имена, &выражение, временный_размер)?;
+            Ok(Тип::Указатель{тип_элемента: Box::new(тип)})
+        }
+        Выражение::Разыменование{ключ, выражение} => {
+            let тип_элемента = скомпилировать_разыменование(пп, имена, текущая_процедура, локальные_имена, ключ, выражение, временный_размер)?;
+            if тип_элемента.примитивный() {
+                прочитать_примитивный_тип(&ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
+            }
+            Ok(тип_элемента)
+        }
         Выражение::УнарныйМинус {ключ, выражение} => {
             let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &выражение, временный_размер)?;
             match тип {
@@ -1237,8 +1755,13 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
                     пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ32Отриц, лок: ключ.лок.clone() });
                     Ok(тип)
                 }
-                Тип::Цел64 => {
+                Тип::Вещ64 => {
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Вещ64Отриц, лок: ключ.лок.clone() });
+                    Ok(тип)
+                }
+                Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
                     пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелОтриц, лок: ключ.лок.clone() });
+                    привести_целое(пп, &ключ.лок, &тип);
                     Ok(тип)
                 }
                 _ => {
@@ -1249,46 +1772,17 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
         }
 
         Выражение::ЦепочкаВызовов {имя, цепочка_аргументов} => {
-            let mut аргументы = цепочка_аргументов.iter();
-            let mut тип_вызываемого = скомпилировать_выражение_вызова_функции_по_имени(пп, имена, текущая_процедура, локальные_имена, имя, &аргументы.next().expect("Цепочка аргументов вызова не может быть пустой").выражения, временный_размер)?;
-
-            for аргументы in аргументы {
-                match &тип_вызываемого {
-                    Тип::Массив{тип_элемента, ..} => {
-                        let [индекс] = &аргументы.выражения[..] else {
-                            диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                            return Err(());
-                        };
-
-                        индексировать_массив(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                        if тип_элемента.примитивный() {
-                            прочитать_примитивный_тип(&аргументы.ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
-                        }
-                        тип_вызываемого = *тип_элемента.clone();
-                    }
-                    Тип::Срез{тип_элемента} => {
-                        let [индекс] = &аргументы.выражения[..] else {
-                            диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Некорректная размерность среза. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                            return Err(());
-                        };
-
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: аргументы.ключ.лок.clone()});
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: аргументы.ключ.лок.clone()});
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: аргументы.ключ.лок.clone()});
-                        индексировать_массив(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                        if тип_элемента.примитивный() {
-                            прочитать_примитивный_тип(&аргументы.ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
-                        }
-                        тип_вызываемого = *тип_элемента.clone();
-                    }
-                    _ => {
-                        диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Невозможно индексировать тип «{тип}»", тип = тип_вызываемого.текст());
-                        return Err(())
-                    }
-                }
-            }
-
-            Ok(тип_вызываемого)
+            let (первые_аргументы, остальные_аргументы) = цепочка_аргументов.split_first().expect("Цепочка аргументов вызова не может быть пустой");
+            let тип_вызываемого = скомпилировать_выражение_вызова_функции_по_имени(пп, имена, текущая_процедура, локальные_имена, имя, &первые_аргументы.выражения, временный_размер)?;
+            скомпилировать_цепочку_индексов(пп, имена, текущая_процедура, локальные_имена, тип_вызываемого, остальные_аргументы, временный_размер)
+        }
+        Выражение::ЦепочкаСтроки{строка, цепочка_аргументов} => {
+            let тип_строки = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &Выражение::Строка(строка.clone()), временный_размер)?;
+            скомпилировать_цепочку_индексов(пп, имена, текущая_процедура, локальные_имена, тип_строки, цепочка_аргументов, временный_размер)
+        }
+        Выражение::Диапазон{ключ, ..} => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Диапазон можно использовать только в скобках после массива или среза, чтобы взять его срез");
+            Err(())
         }
 
         Выражение::Отрицание{ключ, выражение} => {
@@ -1300,6 +1794,49 @@ fn скомпилировать_выражение(пп: &mut ПП, имена:
     }
 }
 
+/// Значение типа `тип_вызываемого` уже лежит на стеке. Применяем к нему
+/// оставшиеся скобки цепочки: индексы элементов или диапазоны срезов.
+fn скомпилировать_цепочку_индексов(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, mut тип_вызываемого: Тип, цепочка_аргументов: &[Аргументы], временный_размер: &mut usize) -> Результат<Тип> {
+    for аргументы in цепочка_аргументов {
+        if let [диапазон @ Выражение::Диапазон{..}] = &аргументы.выражения[..] {
+            тип_вызываемого = скомпилировать_срез_диапазоном(пп, имена, текущая_процедура, локальные_имена, &тип_вызываемого, диапазон, временный_размер)?;
+            continue;
+        }
+        match &тип_вызываемого {
+            Тип::Массив{тип_элемента, размер} => {
+                let [индекс] = &аргументы.выражения[..] else {
+                    диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
+                    return Err(());
+                };
+
+                индексировать_массив(тип_элемента, *размер, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
+                if тип_элемента.примитивный() {
+                    прочитать_примитивный_тип(&аргументы.ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
+                }
+                тип_вызываемого = *тип_элемента.clone();
+            }
+            Тип::Срез{тип_элемента} => {
+                let [индекс] = &аргументы.выражения[..] else {
+                    диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Некорректная размерность среза. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
+                    return Err(());
+                };
+
+                индексировать_срез(тип_элемента, &аргументы.ключ.лок, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
+                if тип_элемента.примитивный() {
+                    прочитать_примитивный_тип(&аргументы.ключ.лок, &mut пп.код, &имена.структуры, &тип_элемента)?;
+                }
+                тип_вызываемого = *тип_элемента.clone();
+            }
+            _ => {
+                диагностика!(&аргументы.ключ.лок, "ОШИБКА", "Невозможно индексировать тип «{тип}»", тип = тип_вызываемого.текст());
+                return Err(())
+            }
+        }
+    }
+
+    Ok(тип_вызываемого)
+}
+
 fn достать_и_проверить_процедуру_печати<'ы>(имена: &'ы Имена, имя_принтера: &str, тип: &Тип, лок: &Лок) -> Результат<&'ы СкомпПроцедура> {
     if let Some(процедура) = имена.процедуры.get(имя_принтера) {
         let арность = 1;
@@ -1326,10 +1863,388 @@ fn достать_и_проверить_процедуру_печати<'ы>(и
     }
 }
 
-fn вызвать_процедуру(лок: Лок, имена: &Имена, процедура: &СкомпПроцедура, код: &mut Vec<Инструкция>, временный_размер: &mut usize) -> Результат<()> {
+fn скомпилировать_аргумент(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, параметр: &СкомпПеременная, аргумент: &Выражение, временный_размер: &mut usize) -> Результат<()> {
+    let тип = if параметр.ссылка {
+        if !является_левым_выражением(аргумент) {
+            диагностика!(аргумент.лок(), "ОШИБКА", "Параметр «{имя}» передаётся по ссылке. Аргументом может быть только переменная, её поле или элемент.", имя = параметр.имя.текст);
+            диагностика!(&параметр.имя.лок, "ИНФО", "Параметр определен здесь");
+            return Err(());
+        }
+        скомпилировать_левое_выражение(пп, имена, текущая_процедура, локальные_имена, аргумент, временный_размер)?
+    } else {
+        скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, аргумент, временный_размер)?
+    };
+    проверить_тип_значения(пп, аргумент, &параметр.тип, тип)
+}
+
+/// Значения целых типов уже слова лежат на стеке расширенными до слова:
+/// беззнаковые нулями, знаковые знаковым битом. Если значение могло выйти
+/// за пределы типа, его нужно привести обратно.
+fn привести_целое(пп: &mut ПП, лок: &Лок, тип: &Тип) {
+    let (биты, знаковый) = match тип {
+        Тип::Нат8 => (8, false),
+        Тип::Нат16 => (16, false),
+        Тип::Нат32 => (32, false),
+        Тип::Цел8 => (8, true),
+        Тип::Цел16 => (16, true),
+        Тип::Цел32 => (32, true),
+        _ => return,
+    };
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное((1 << биты) - 1), лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::БитИ, лок: лок.clone() });
+    if знаковый {
+        // (x либо знак) - знак распространяет знаковый бит на всё слово.
+        let знак = 1 << (биты - 1);
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(знак), лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::БитЛибо, лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(знак), лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::НатВычитание, лок: лок.clone() });
+    }
+}
+
+/// Любое значение типа `тип_значения` представимо в целом типе `тип`.
+fn целый_тип_вмещает(тип: &Тип, тип_значения: &Тип) -> bool {
+    match (тип.диапазон_целого(), тип_значения.диапазон_целого()) {
+        (Some((минимум, максимум)), Some((минимум_значения, максимум_значения))) => минимум <= минимум_значения && максимум_значения <= максимум,
+        _ => false,
+    }
+}
+
+/// Значение выражения, составленного только из целых литералов без
+/// суффикса, например «10» или «-(2*3)». Тип такого выражения не
+/// зафиксирован и уточняется по месту использования.
+fn значение_целого_литерала(выражение: &Выражение) -> Option<i128> {
+    match выражение {
+        Выражение::ЦелЧисло(_, число) => Some(*число as u64 as i128),
+        Выражение::УнарныйМинус{выражение, ..} => значение_целого_литерала(выражение).map(|значение| -значение),
+        Выражение::Биноп{вид, левое, правое, ..} => {
+            let левое = значение_целого_литерала(левое)?;
+            let правое = значение_целого_литерала(правое)?;
+            // Деление и остаток не сворачиваем: для отрицательных
+            // промежуточных значений знаковый и беззнаковый результат
+            // различаются.
+            match вид {
+                ВидБинопа::Сложение => левое.checked_add(правое),
+                ВидБинопа::Вычитание => левое.checked_sub(правое),
+                ВидБинопа::Умножение => левое.checked_mul(правое),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Литерал без суффикса по умолчанию имеет тип «цел64», но там, где
+/// ожидается другой целочисленный тип, принимает его, если значение в
+/// него помещается. Представление на стеке от этого не меняется.
+fn уточнить_тип_литерала(выражение: &Выражение, тип: Тип, ожидаемый_тип: &Тип) -> Результат<Тип> {
+    // Символ из ASCII по умолчанию имеет тип «нат8».
+    if let (Выражение::Символ(..), Тип::Символ) = (выражение, ожидаемый_тип) {
+        return Ok(Тип::Символ);
+    }
+    // Диапазон проверяем и для «цел64»: разборщик хранит литерал как i64,
+    // поэтому слишком большое значение молча станет отрицательным.
+    let (Some(значение), Some((минимум, максимум))) = (значение_целого_литерала(выражение), ожидаемый_тип.диапазон_целого()) else {
+        return Ok(тип);
+    };
+    if значение < минимум || значение > максимум {
+        диагностика!(выражение.лок(), "ОШИБКА", "Значение {значение} не помещается в тип «{тип}». Допустимы значения от {минимум} до {максимум}",
+                     тип = ожидаемый_тип.текст());
+        return Err(());
+    }
+    Ok(ожидаемый_тип.clone())
+}
+
+/// Литерал, которому контекст не подсказал тип, остаётся «цел64» и должен в
+/// него помещаться.
+fn проверить_литерал_по_умолчанию(выражение: &Выражение, тип: &Тип) -> Результат<()> {
+    уточнить_тип_литерала(выражение, тип.clone(), тип).map(|_| ())
+}
+
+/// Вещественный литерал без суффикса по умолчанию имеет тип «вещ32», но
+/// там, где ожидается «вещ64», принимает его. Литерал к этому моменту уже
+/// скомпилирован, поэтому его инструкции переписываются на двойную точность.
+fn уточнить_вещественный_литерал(пп: &mut ПП, выражение: &Выражение, тип: Тип, ожидаемый_тип: &Тип) -> Тип {
+    if !matches!((&тип, ожидаемый_тип), (Тип::Вещ32, Тип::Вещ64)) || значение_вещественного_литерала(выражение).is_none() {
+        return тип;
+    }
+    let заменить = |пп: &mut ПП, лок: &Лок, подходит: fn(&ВидИнструкции) -> bool, новый: ВидИнструкции| {
+        let инструкция = пп.код.iter_mut().rev()
+            .find(|инструкция| &инструкция.лок == лок && подходит(&инструкция.вид))
+            .expect("Литерал должен быть уже скомпилирован");
+        инструкция.вид = новый;
+    };
+    match выражение {
+        Выражение::ВещЧисло(лексема, число) => {
+            let число = u64::from_le_bytes(число.to_le_bytes()) as usize;
+            заменить(пп, &лексема.лок, |вид| matches!(вид, ВидИнструкции::Натуральное(_)), ВидИнструкции::Натуральное(число));
+        }
+        Выражение::УнарныйМинус{ключ, выражение} => {
+            уточнить_вещественный_литерал(пп, выражение, тип, ожидаемый_тип);
+            заменить(пп, &ключ.лок, |вид| matches!(вид, ВидИнструкции::Вещ32Отриц), ВидИнструкции::Вещ64Отриц);
+        }
+        _ => unreachable!(),
+    }
+    Тип::Вещ64
+}
+
+/// Значение вещественного литерала без суффикса, например «1.5» или «-1.5».
+fn значение_вещественного_литерала(выражение: &Выражение) -> Option<f64> {
+    match выражение {
+        Выражение::ВещЧисло(_, число) => Some(*число),
+        Выражение::УнарныйМинус{выражение, ..} => значение_вещественного_литерала(выражение).map(|значение| -значение),
+        _ => None,
+    }
+}
+
+fn проверить_тип_значения(пп: &mut ПП, значение: &Выражение, ожидаемый_тип: &Тип, тип_значения: Тип) -> Результат<()> {
+    let тип_значения = уточнить_тип_литерала(значение, тип_значения, ожидаемый_тип)?;
+    let тип_значения = уточнить_вещественный_литерал(пп, значение, тип_значения, ожидаемый_тип);
+    проверить_типы(значение.лок(), ожидаемый_тип, &тип_значения)
+}
+
+/// Инструкция, сравнивающая два значения примитивного типа `тип`. Для
+/// остальных типов и операций, не являющихся сравнением, возвращает `None`.
+fn инструкция_сравнения(тип: &Тип, вид: &ВидБинопа) -> Option<ВидИнструкции> {
+    if matches!(вид, ВидБинопа::Равно) {
+        return match тип {
+            Тип::Вещ32 => Some(ВидИнструкции::Вещ32Равно),
+            Тип::Вещ64 => Some(ВидИнструкции::Вещ64Равно),
+            _ if тип.примитивный() => Some(ВидИнструкции::НатРавно),
+            _ => None,
+        };
+    }
+    match (тип, вид) {
+        (Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Символ | Тип::Указатель{..}, ВидБинопа::Меньше) => Some(ВидИнструкции::НатМеньше),
+        (Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Символ | Тип::Указатель{..}, ВидБинопа::МеньшеРавно) => Some(ВидИнструкции::НатМеньшеРавно),
+        (Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Символ | Тип::Указатель{..}, ВидБинопа::Больше) => Some(ВидИнструкции::НатБольше),
+        (Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Символ | Тип::Указатель{..}, ВидБинопа::БольшеРавно) => Some(ВидИнструкции::НатБольшеРавно),
+        (Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64, ВидБинопа::Меньше) => Some(ВидИнструкции::ЦелМеньше),
+        (Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64, ВидБинопа::МеньшеРавно) => Some(ВидИнструкции::ЦелМеньшеРавно),
+        (Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64, ВидБинопа::Больше) => Some(ВидИнструкции::ЦелБольше),
+        (Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64, ВидБинопа::БольшеРавно) => Some(ВидИнструкции::ЦелБольшеРавно),
+        (Тип::Вещ32, ВидБинопа::Меньше) => Some(ВидИнструкции::Вещ32Меньше),
+        (Тип::Вещ32, ВидБинопа::МеньшеРавно) => Some(ВидИнструкции::Вещ32МеньшеРавно),
+        (Тип::Вещ32, ВидБинопа::Больше) => Some(ВидИнструкции::Вещ32Больше),
+        (Тип::Вещ32, ВидБинопа::БольшеРавно) => Some(ВидИнструкции::Вещ32БольшеРавно),
+        (Тип::Вещ64, ВидБинопа::Меньше) => Some(ВидИнструкции::Вещ64Меньше),
+        (Тип::Вещ64, ВидБинопа::МеньшеРавно) => Some(ВидИнструкции::Вещ64МеньшеРавно),
+        (Тип::Вещ64, ВидБинопа::Больше) => Some(ВидИнструкции::Вещ64Больше),
+        (Тип::Вещ64, ВидБинопа::БольшеРавно) => Some(ВидИнструкции::Вещ64БольшеРавно),
+        _ => None,
+    }
+}
+
+/// Литерал в одном из операндов бинарной операции принимает тип другого
+/// операнда.
+fn уточнить_типы_операндов(пп: &mut ПП, левое: &Выражение, левый_тип: Тип, правое: &Выражение, правый_тип: Тип) -> Результат<(Тип, Тип)> {
+    let правый_тип = уточнить_тип_литерала(правое, правый_тип, &левый_тип)?;
+    let правый_тип = уточнить_вещественный_литерал(пп, правое, правый_тип, &левый_тип);
+    let левый_тип = уточнить_тип_литерала(левое, левый_тип, &правый_тип)?;
+    let левый_тип = уточнить_вещественный_литерал(пп, левое, левый_тип, &правый_тип);
+    Ok((левый_тип, правый_тип))
+}
+
+/// Сокращённое вычисление «и» (`пропустить_на_ложь`) и «или». Левое
+/// логическое значение уже лежит на стеке. Если оно уже определяет результат,
+/// то правое выражение не вычисляется.
+///
+/// Временные данные правого выражения освобождаются сразу, т.к. на пути,
+/// где оно не вычисляется, их попросту нет.
+fn скомпилировать_сокращённую_логику(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, ключ: &Лексема, правое: &Выражение, пропустить_на_ложь: bool) -> Результат<Тип> {
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: ключ.лок.clone() });
+    if пропустить_на_ложь {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ.лок.clone() });
+    }
+    let точка_условного_прыжка = пп.код.len();
+    пп.код.push(Инструкция{ вид: ВидИнструкции::УсловныйПрыжок(0), лок: ключ.лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Вытолкнуть, лок: ключ.лок.clone() });
+
+    let mut временный_размер_правого = 0;
+    let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, правое, &mut временный_размер_правого)?;
+    проверить_типы(правое.лок(), &Тип::Лог, &правый_тип)?;
+    if временный_размер_правого > 0 {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер_правого as u64), лок: ключ.лок.clone() });
+    }
+
+    let конец = пп.код.len();
+    пп.код[точка_условного_прыжка].вид = ВидИнструкции::УсловныйПрыжок(конец);
+    Ok(Тип::Лог)
+}
+
+/// Срезы и массивы сравниваются по содержимому. Адрес левого операнда уже
+/// лежит на стеке. Оба операнда заменяются адресами и размерами своих
+/// элементов, «СравнитьПамять» оставляет на стеке -1, 0 или 1, и это
+/// значение сравнивается с нулём инструкцией `сравнение`.
+fn скомпилировать_сравнение_содержимого(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, ключ: &Лексема, левый_тип: &Тип, правое: &Выражение, временный_размер: &mut usize, упорядочить: bool, сравнение: ВидИнструкции) -> Результат<Тип> {
+    let (Тип::Срез{тип_элемента} | Тип::Массив{тип_элемента, ..}) = левый_тип else {
+        unreachable!("Сравнивать по содержимому можно только срезы и массивы");
+    };
+    // Побайтовый порядок совпадает с порядком элементов только для байтов.
+    if упорядочить && **тип_элемента != Тип::Нат8 {
+        диагностика!(&ключ.лок, "ОШИБКА", "Упорядочивать можно только срезы и массивы элементов типа «нат8», но повстречался тип «{тип}»", тип = левый_тип.текст());
+        return Err(());
+    }
+    развернуть_содержимое(пп, имена, &ключ.лок, левый_тип);
+    let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, правое, временный_размер)?;
+    проверить_типы(правое.лок(), левый_тип, &правый_тип)?;
+    развернуть_содержимое(пп, имена, &ключ.лок, &правый_тип);
+    пп.код.push(Инструкция{ вид: ВидИнструкции::СравнитьПамять, лок: ключ.лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Целое(0), лок: ключ.лок.clone() });
+    пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
+    Ok(Тип::Лог)
+}
+
+/// Адрес среза или массива уже лежит на стеке. Заменяем его адресом
+/// элементов и их размером в байтах.
+fn развернуть_содержимое(пп: &mut ПП, имена: &Имена, лок: &Лок, тип: &Тип) {
+    match тип {
+        Тип::Срез{тип_элемента} => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ), лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_элемента.размер(&имена.структуры)), лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: лок.clone() });
+        }
+        Тип::Массив{..} => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип.размер(&имена.структуры)), лок: лок.clone() });
+        }
+        _ => unreachable!("Развернуть можно только срез или массив"),
+    }
+}
+
+/// Значение корня вилки уже лежит на стеке. Сравниваем его с образцом так
+/// же, как это делает «=»: срезы по содержимому, структуры и массивы
+/// побайтово, вещественные как числа, чтобы -0.0 совпадал с 0.0.
+fn скомпилировать_равенство_образца(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, ключ: &Лексема, тип_корня: &Тип, образец: &Выражение, временный_размер: &mut usize) -> Результат<()> {
+    if let Тип::Срез{..} = тип_корня {
+        скомпилировать_сравнение_содержимого(пп, имена, текущая_процедура, локальные_имена, ключ, тип_корня, образец, временный_размер, false, ВидИнструкции::НатРавно)?;
+        return Ok(());
+    }
+    let тип_образца = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, образец, временный_размер)?;
+    проверить_тип_значения(пп, образец, тип_корня, тип_образца)?;
+    if let Some(сравнение) = инструкция_сравнения(тип_корня, &ВидБинопа::Равно) {
+        пп.код.push(Инструкция{ вид: сравнение, лок: ключ.лок.clone() });
+    } else {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_корня.размер(&имена.структуры)), лок: ключ.лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ПамятьРавно, лок: ключ.лок.clone() });
+    }
+    Ok(())
+}
+
+/// Значение образца вилки, известное на этапе компиляции.
+#[derive(PartialEq)]
+enum ИзвестноеЗначение {
+    Число(i128),
+    Текст(String),
+}
+
+fn известное_значение(имена: &Имена, локальные_имена: &ЛокальныеИмена, выражение: &Выражение, тип_корня: &Тип) -> Результат<Option<ИзвестноеЗначение>> {
+    let значение = match выражение {
+        Выражение::НатЧисло(_, число) => Some(ИзвестноеЗначение::Число(*число as i128)),
+        Выражение::Символ(_, символ) => Some(ИзвестноеЗначение::Число(*символ as i128)),
+        Выражение::Лог(_, значение) => Some(ИзвестноеЗначение::Число(*значение as i128)),
+        Выражение::Строка(лексема) => Some(ИзвестноеЗначение::Текст(лексема.текст.clone())),
+        Выражение::Биноп{вид: ВидБинопа::Поле, левое, правое, ..} => значение_перечисления(имена, левое, правое)?
+            .map(|(_, значение)| ИзвестноеЗначение::Число(значение as i128)),
+        Выражение::Идент(имя) => найти_константу_по_имени(&имя.текст, имена, Some(локальные_имена))
+            .filter(|константа| константа.тип == *тип_корня && !matches!(константа.тип, Тип::Вещ32 | Тип::Вещ64))
+            .and_then(|константа| match константа.тип.примитивное_знаковое_чтение()? {
+                true => Some(ИзвестноеЗначение::Число(константа.значение_или_адрес as i64 as i128)),
+                false => Some(ИзвестноеЗначение::Число(константа.значение_или_адрес as i128)),
+            }),
+        _ => значение_целого_литерала(выражение).map(ИзвестноеЗначение::Число),
+    };
+    Ok(значение)
+}
+
+/// Ищем образцы, которые никогда не сработают: повторы, значения, уже
+/// покрытые предыдущими диапазонами, и пустые диапазоны. Проверяются только
+/// значения, известные на этапе компиляции.
+fn проверить_образцы_вилки(имена: &Имена, локальные_имена: &ЛокальныеИмена, тип_корня: &Тип, ветки: &[ВеткаВилки]) -> Результат<()> {
+    let mut проверенные_отрезки: Vec<(i128, i128, &Лок)> = Vec::new();
+    let mut проверенные_тексты: Vec<(String, &Лок)> = Vec::new();
+    for образец in ветки.iter().flat_map(|ветка| ветка.образцы.iter()) {
+        let (начало, конец) = match образец {
+            ОбразецВилки::Значение(выражение) => match известное_значение(имена, локальные_имена, выражение, тип_корня)? {
+                Some(ИзвестноеЗначение::Число(число)) => (число, число),
+                Some(ИзвестноеЗначение::Текст(текст)) => {
+                    if let Some((_, лок)) = проверенные_тексты.iter().find(|(проверенный, _)| *проверенный == текст) {
+                        диагностика!(образец.лок(), "ОШИБКА", "Значение уже проверяется в этой вилке");
+                        диагностика!(лок, "ИНФО", "Впервые оно проверяется здесь");
+                        return Err(());
+                    }
+                    проверенные_тексты.push((текст, образец.лок()));
+                    continue;
+                }
+                None => continue,
+            },
+            ОбразецВилки::Диапазон{от, ключ, до} => {
+                let от = известное_значение(имена, локальные_имена, от, тип_корня)?;
+                let до = известное_значение(имена, локальные_имена, до, тип_корня)?;
+                let (Some(ИзвестноеЗначение::Число(от)), Some(ИзвестноеЗначение::Число(до))) = (от, до) else {
+                    continue;
+                };
+                if от > до {
+                    диагностика!(&ключ.лок, "ОШИБКА", "Пустой диапазон: начало {от} больше конца {до}");
+                    return Err(());
+                }
+                (от, до)
+            }
+        };
+        if начало == конец {
+            if let Some((_, _, лок)) = проверенные_отрезки.iter().find(|(от, до, _)| *от == начало && *до == конец) {
+                диагностика!(образец.лок(), "ОШИБКА", "Значение уже проверяется в этой вилке");
+                диагностика!(лок, "ИНФО", "Впервые оно проверяется здесь");
+                return Err(());
+            }
+        }
+        // Двигаемся по значениям образца, перепрыгивая через отрезки,
+        // которые уже проверены выше.
+        let mut текущее = начало;
+        while let Some((_, до, _)) = проверенные_отрезки.iter().find(|(от, до, _)| *от <= текущее && текущее <= *до) {
+            if *до >= конец {
+                диагностика!(образец.лок(), "ОШИБКА", "Образец недостижим: все его значения уже проверены в предыдущих образцах вилки");
+                return Err(());
+            }
+            текущее = до + 1;
+        }
+        проверенные_отрезки.push((начало, конец, образец.лок()));
+    }
+    Ok(())
+}
+
+/// Указатель и смещение уже лежат на стеке. Смещение измеряется в
+/// элементах, поэтому домножаем его на размер элемента.
+fn масштабировать_смещение_указателя(пп: &mut ПП, имена: &Имена, смещение: &Выражение, тип_смещения: &Тип, тип_элемента: &Тип) -> Результат<()> {
+    let размер_элемента = тип_элемента.размер(&имена.структуры);
+    match тип_смещения {
+        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(размер_элемента), лок: смещение.лок().clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: смещение.лок().clone() });
+        }
+        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Целое(размер_элемента as i64), лок: смещение.лок().clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелУмножение, лок: смещение.лок().clone() });
+        }
+        _ => {
+            диагностика!(смещение.лок(), "ОШИБКА", "Указатель можно смещать только на целое или натуральное число, но повстречался тип «{тип}»", тип = тип_смещения.текст());
+            return Err(());
+        }
+    }
+    Ok(())
+}
+
+fn вызвать_процедуру(лок: Лок, имена: &Имена, процедура: &СкомпПроцедура, пп: &mut ПП, временный_размер: &mut usize) -> Результат<()> {
     match &процедура.точка_входа {
         &ТочкаВхода::Внутреняя{адрес} => {
-            код.push(Инструкция {
+            пп.заплатки_вызовов.push((пп.код.len(), процедура.ключ()));
+            пп.код.push(Инструкция {
                 вид: ВидИнструкции::ВнутреннийВызов(адрес),
                 лок: лок.clone(),
             });
@@ -1341,16 +2256,21 @@ fn вызвать_процедуру(лок: Лок, имена: &Имена, п
         },
         &ТочкаВхода::Внешняя{индекс} => {
             for параметр in &процедура.параметры {
-                if параметр.тип != Тип::Нат64 && параметр.тип != Тип::Цел64 {
+                if !параметр.ссылка && !matches!(параметр.тип, Тип::Нат64 | Тип::Цел64 | Тип::Вещ32 | Тип::Вещ64 | Тип::Указатель{..}) {
                     сделать!(&лок, "Вызов внешней процедуры с параметрами типа «{тип}»", тип = параметр.тип.текст());
                     return Err(())
                 }
             }
 
-            код.push(Инструкция {
+            пп.код.push(Инструкция {
                 вид: ВидИнструкции::ВнешнийВызов{
                     индекс,
-                    арность: процедура.параметры.len(),
+                    // Параметр по ссылке передаётся как адрес.
+                    параметры: процедура.параметры.iter().map(|параметр| if параметр.ссылка {
+                        Тип::Указатель{тип_элемента: Box::new(параметр.тип.clone())}
+                    } else {
+                        параметр.тип.clone()
+                    }).collect(),
                     результат: процедура.результат.clone().map(|результат| результат.тип),
                 },
                 лок,
@@ -1360,17 +2280,150 @@ fn вызвать_процедуру(лок: Лок, имена: &Имена, п
     Ok(())
 }
 
-fn индексировать_массив(тип_элемента: &Тип, пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, индекс: &Выражение, временный_размер: &mut usize) -> Результат<()> {
-    // СДЕЛАТЬ: проверку входа за границы массива/среза. Включая отрицательные числа.
+/// Адрес начала массива уже лежит на стеке.
+fn индексировать_массив(тип_элемента: &Тип, количество_элементов: usize, пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, индекс: &Выражение, временный_размер: &mut usize) -> Результат<()> {
+    if !пп.без_проверок_границ {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(количество_элементов), лок: индекс.лок().clone() });
+    }
+    скомпилировать_смещение_элемента(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)
+}
+
+/// Адрес самого среза (а не его элементов) уже лежит на стеке.
+fn индексировать_срез(тип_элемента: &Тип, лок: &Лок, пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, индекс: &Выражение, временный_размер: &mut usize) -> Результат<()> {
+    if !пп.без_проверок_границ {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: лок.clone()});
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ), лок: лок.clone()});
+        пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone()});
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone()});
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone()});
+    }
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: лок.clone()});
+    пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone()});
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone()});
+    if !пп.без_проверок_границ {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone()});
+    }
+    скомпилировать_смещение_элемента(тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)
+}
+
+/// Граница диапазона, как и индекс, может быть любого целого типа.
+/// Отрицательная граница при беззнаковом сравнении оказывается больше любого
+/// количества элементов и не проходит проверку диапазона.
+fn скомпилировать_границу_диапазона(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, граница: &Выражение, временный_размер: &mut usize) -> Результат<()> {
+    let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, граница, временный_размер)?;
+    match уточнить_тип_литерала(граница, тип, &Тип::Нат64)? {
+        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => Ok(()),
+        тип => {
+            диагностика!(граница.лок(), "ОШИБКА", "Невозможно брать срез диапазоном с границами типа «{тип}»", тип = тип.текст());
+            Err(())
+        }
+    }
+}
+
+/// Адрес массива или самого среза уже лежит на стеке. Заменяем его адресом
+/// нового среза с элементами в границах `диапазон` включительно.
+fn скомпилировать_срез_диапазоном(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, тип: &Тип, диапазон: &Выражение, временный_размер: &mut usize) -> Результат<Тип> {
+    let Выражение::Диапазон{ключ, от, до} = диапазон else {
+        unreachable!("Срез можно взять только диапазоном");
+    };
+    let лок = &ключ.лок;
+    // На стеке: адрес элементов, их количество.
+    let тип_элемента = match тип {
+        Тип::Массив{тип_элемента, размер} => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(*размер), лок: лок.clone() });
+            тип_элемента
+        }
+        Тип::Срез{тип_элемента} => {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ), лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone() });
+            тип_элемента
+        }
+        _ => {
+            диагностика!(лок, "ОШИБКА", "Взять срез диапазоном можно только у массива или среза, но повстречался тип «{тип}»", тип = тип.текст());
+            return Err(());
+        }
+    };
+
+    // На стеке: адрес элементов, их количество, начало, конец. Конец
+    // включительно, только если указан.
+    if let Some(от) = от {
+        скомпилировать_границу_диапазона(пп, имена, текущая_процедура, локальные_имена, от, временный_размер)?;
+    } else {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(0), лок: лок.clone() });
+    }
+    if let Some(до) = до {
+        скомпилировать_границу_диапазона(пп, имена, текущая_процедура, локальные_имена, до, временный_размер)?;
+    } else {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone() });
+    }
+    let включительно = до.is_some();
+    if пп.без_проверок_границ {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Вытолкнуть, лок: лок.clone() });
+        if включительно {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(1), лок: лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone() });
+        }
+    } else {
+        // Единица прибавляется к концу только после проверки, иначе
+        // наибольший «нат» переполнится.
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ПроверитьДиапазон{включительно}, лок: лок.clone() });
+    }
+
+    // На стеке: адрес элементов, начало, конец. Превращаем их в размер
+    // нового среза и адрес его первого элемента.
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Обменять, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::НатВычитание, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Прокрутить, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_элемента.размер(&имена.структуры)), лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: лок.clone() });
+
+    let тип_среза = Тип::Срез{тип_элемента: тип_элемента.clone()};
+    пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(тип_среза.размер(&имена.структуры) as u64), лок: лок.clone() });
+    *временный_размер += тип_среза.размер(&имена.структуры);
+    пп.код.push(Инструкция{ вид: ВидИнструкции::ВершинаСтека(СРЕЗ_АДРЕС_СМЕЩЕНИЕ as i32), лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::ВершинаСтека(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ as i32), лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::ВершинаСтека(0), лок: лок.clone() });
+    Ok(тип_среза)
+}
+
+/// На стеке лежит адрес начала элементов, а если проверки границ включены,
+/// то и количество элементов поверх него.
+fn скомпилировать_смещение_элемента(тип_элемента: &Тип, пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, индекс: &Выражение, временный_размер: &mut usize) -> Результат<()> {
     let тип_индекса = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
     match тип_индекса {
-        Тип::Нат64 => {
+        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 => {
+            if !пп.без_проверок_границ {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ПроверитьГраницы, лок: индекс.лок().clone() });
+            }
             пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_элемента.размер(&имена.структуры)), лок: индекс.лок().clone() });
             пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: индекс.лок().clone() });
             пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: индекс.лок().clone() });
             Ok(())
         }
-        Тип::Цел64 => {
+        Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => {
+            // Отрицательный индекс при беззнаковом сравнении оказывается
+            // больше любого количества элементов.
+            if !пп.без_проверок_границ {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ПроверитьГраницы, лок: индекс.лок().clone() });
+            }
             пп.код.push(Инструкция{ вид: ВидИнструкции::Целое(тип_элемента.размер(&имена.структуры) as i64), лок: индекс.лок().clone() });
             пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелУмножение, лок: индекс.лок().clone() });
             пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: индекс.лок().clone() });
@@ -1386,6 +2439,12 @@ fn индексировать_массив(тип_элемента: &Тип, п
 enum Локация {
     Глобальная,
     Стековая,
+    /// Параметр, переданный по ссылке, либо элемент цикла «для … в …». На
+    /// стеке лежит адрес переменной.
+    Ссылочная,
+    /// Константа непримитивного типа, либо глобальная переменная с
+    /// константным начальным значением. Лежит в инициализированных данных.
+    ИницДанные,
 }
 
 impl Локация {
@@ -1398,11 +2457,43 @@ impl Локация {
             Локация::Стековая => {
                 пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение), лок: лок.clone() });
             }
+            Локация::Ссылочная => {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение), лок: лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: лок.clone() });
+            }
+            Локация::ИницДанные => {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ГлобальныеДанные(смещение), лок: лок.clone() });
+            }
         }
     }
 }
 
+/// Значение перечисления записывается как «Перечисление.Значение». Если
+/// левая часть не является именем перечисления, возвращает None.
+fn значение_перечисления(имена: &Имена, левое: &Выражение, правое: &Выражение) -> Результат<Option<(Тип, usize)>> {
+    let Выражение::Идент(имя_перечисления) = левое else {
+        return Ok(None);
+    };
+    let Some(перечисление) = имена.перечисления.get(&имя_перечисления.текст) else {
+        return Ok(None);
+    };
+    let Выражение::Идент(имя_значения) = правое else {
+        диагностика!(правое.лок(), "ОШИБКА", "Ожидалось имя значения перечисления «{имя}»", имя = имя_перечисления.текст);
+        return Err(());
+    };
+    if let Some(значение) = перечисление.значение(&имя_значения.текст) {
+        Ok(Some((Тип::Перечисление(имя_перечисления.текст.clone()), значение)))
+    } else {
+        диагностика!(&имя_значения.лок, "ОШИБКА", "У перечисления «{перечисление}» отсутствует значение «{значение}»", перечисление = имя_перечисления.текст, значение = имя_значения.текст);
+        Err(())
+    }
+}
+
 fn найти_константу_по_имени<'ы>(имя: &str, имена: &'ы Имена, локальные_имена: Option<&'ы ЛокальныеИмена>) -> Option<&'ы СкомпКонстанта> {
+    if let Some((модуль, имя)) = имя.split_once('.') {
+        return имена.константы.get(имя).filter(|константа| константа.модуль.as_deref() == Some(модуль));
+    }
+
     if let Some(локальные_имена) = локальные_имена {
         if let Some(константа) = локальные_имена.найти_константу_по_имени(имя) {
             return Some(константа);
@@ -1416,22 +2507,130 @@ fn найти_константу_по_имени<'ы>(имя: &str, имена:
     None
 }
 
+/// «модуль.имя» разбирается как обращение к полю. Если слева стоит имя
+/// включённого модуля, не перекрытое переменной или константой, то
+/// превращаем выражение в обращение к имени «модуль.имя».
+fn разрешить_имя_модуля(имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, выражение: &Выражение) -> Option<Выражение> {
+    let Выражение::Биноп{вид: ВидБинопа::Поле, левое, правое, ..} = выражение else {
+        return None;
+    };
+    let Выражение::Идент(модуль) = левое.as_ref() else {
+        return None;
+    };
+    if !имена.модули.contains(&модуль.текст) {
+        return None;
+    }
+    if найти_переменную_по_имени(&модуль.текст, имена, текущая_процедура, локальные_имена).is_some() ||
+        найти_константу_по_имени(&модуль.текст, имена, Some(локальные_имена)).is_some()
+    {
+        return None;
+    }
+    let полное_имя = |имя: &Лексема| Лексема {
+        вид: ВидЛексемы::Идент,
+        текст: format!("{модуль}.{имя}", модуль = модуль.текст, имя = имя.текст),
+        лок: модуль.лок.clone(),
+    };
+    match правое.as_ref() {
+        Выражение::Идент(имя) => Some(Выражение::Идент(полное_имя(имя))),
+        Выражение::ЦепочкаВызовов{имя, цепочка_аргументов} => Some(Выражение::ЦепочкаВызовов {
+            имя: полное_имя(имя),
+            цепочка_аргументов: цепочка_аргументов.clone(),
+        }),
+        _ => None,
+    }
+}
+
 fn найти_переменную_по_имени<'ы>(имя: &str, имена: &'ы Имена, текущая_процедура: &'ы СкомпПроцедура, локальные_имена: &'ы ЛокальныеИмена) -> Option<(&'ы СкомпПеременная, Локация)> {
     if let Some(переменная) = локальные_имена.найти_переменную_по_имени(имя) {
+        if переменная.ссылка {
+            return Some((переменная, Локация::Ссылочная));
+        }
         return Some((переменная, Локация::Стековая));
     }
 
     if let Some(параметр) = текущая_процедура.параметры.iter().find(|параметр| параметр.имя.текст == имя) {
+        if параметр.ссылка {
+            return Some((параметр, Локация::Ссылочная));
+        }
         return Some((параметр, Локация::Стековая));
     }
 
     if let Some(переменная) = имена.переменные.get(имя) {
+        if переменная.в_иниц_данных {
+            return Some((переменная, Локация::ИницДанные));
+        }
         return Some((переменная, Локация::Глобальная));
     }
 
     None
 }
 
+/// Найти переменную или константу непримитивного типа, т.е. то, что лежит
+/// в памяти и имеет адрес. Используется там, где значение разрешено только
+/// читать, например при индексировании.
+fn найти_адресуемое_имя(имя: &str, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена) -> Option<(Тип, Локация, i32)> {
+    if let Some((переменная, локация)) = найти_переменную_по_имени(имя, имена, текущая_процедура, локальные_имена) {
+        return Some((переменная.тип.clone(), локация, переменная.смещение));
+    }
+    if let Some(константа) = найти_константу_по_имени(имя, имена, Some(локальные_имена)) {
+        if !константа.тип.примитивный() {
+            return Some((константа.тип.clone(), Локация::ИницДанные, константа.значение_или_адрес as i32));
+        }
+    }
+    None
+}
+
+/// Левая версия `скомпилировать_цепочку_индексов`: оставляет на стеке
+/// адрес элемента, а не его значение.
+fn скомпилировать_левую_цепочку_индексов(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, лок: &Лок, mut тип: Тип, цепочка_аргументов: &[Аргументы], временный_размер: &mut usize) -> Результат<Тип> {
+    for (номер, аргументы) in цепочка_аргументов.iter().enumerate() {
+        if let [диапазон @ Выражение::Диапазон{..}] = &аргументы.выражения[..] {
+            // Срез диапазоном лежит во временной памяти, так что
+            // присваивание ему самому ничего бы не изменило.
+            if номер + 1 == цепочка_аргументов.len() {
+                диагностика!(диапазон.лок(), "ОШИБКА", "Срез диапазоном не может находиться в левой части присваивания. Присваивать можно только его элементам.");
+                return Err(());
+            }
+            тип = скомпилировать_срез_диапазоном(пп, имена, текущая_процедура, локальные_имена, &тип, диапазон, временный_размер)?;
+            continue;
+        }
+        match &тип {
+            Тип::Массив{тип_элемента, размер} => {
+                if аргументы.выражения.len() != 1 {
+                    диагностика!(лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
+                    return Err(());
+                };
+                let индекс = &аргументы.выражения[0];
+                индексировать_массив(&*тип_элемента, *размер, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
+                тип = *тип_элемента.clone();
+            }
+            Тип::Срез{тип_элемента} => {
+                if аргументы.выражения.len() != 1 {
+                    диагностика!(лок, "ОШИБКА", "Некорректная размерность среза. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
+                    return Err(());
+                };
+                let индекс = &аргументы.выражения[0];
+                индексировать_срез(&*тип_элемента, лок, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
+                тип = *тип_элемента.clone();
+            }
+            _ => {
+                диагностика!(лок, "ОШИБКА", "Не возможно индексировать переменные тип «{тип}»", тип = тип.текст());
+                return Err(());
+            }
+        }
+    }
+    Ok(тип)
+}
+
+fn проверить_изменяемость(переменная: &СкомпПеременная, имя: &Лексема) -> Результат<()> {
+    if переменная.только_чтение {
+        диагностика!(&имя.лок, "ОШИБКА", "Элемент «{имя}» ссылается на строковый литерал или константу. Его нельзя изменять, передавать по ссылке или брать его адрес.", имя = имя.текст);
+        диагностика!(&переменная.имя.лок, "ИНФО", "Элемент определён здесь");
+        return Err(());
+    }
+    Ok(())
+}
+
 /// Левое выражение - это выражение по левой стороне от присваивания:
 ///
 /// <левое> := <правое>;
@@ -1451,17 +2650,30 @@ fn скомпилировать_левое_выражение(пп: &mut ПП, 
             диагностика!(&лексема.лок, "ОШИБКА", "Натуральное число не может находиться в левой части присваивания");
             Err(())
         }
-        Выражение::ВещЧисло(лексема, _) => {
+        Выражение::ВещЧисло(лексема, _) | Выражение::Вещ64Число(лексема, _) => {
             диагностика!(&лексема.лок, "ОШИБКА", "Вещественное число не может находиться в левой части присваивания");
             Err(())
         }
+        Выражение::Символ(лексема, _) => {
+            диагностика!(&лексема.лок, "ОШИБКА", "Символ не может находиться в левой части присваивания");
+            Err(())
+        }
         Выражение::Строка(лексема) => {
             // А мы точно хотим разрешать подобные вещи?
             сделать!(&лексема.лок, "Присваивание строковых литералов");
             Err(())
         }
+        Выражение::ЦепочкаСтроки{строка, ..} => {
+            диагностика!(&строка.лок, "ОШИБКА", "Строковые литералы неизменяемы. Их элементы не могут находиться в левой части присваивания");
+            Err(())
+        }
+        Выражение::Диапазон{ключ, ..} => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Диапазон не может находиться в левой части присваивания");
+            Err(())
+        }
         Выражение::Идент(имя) => {
             if let Some((переменная, локация)) = найти_переменную_по_имени(&имя.текст, имена, текущая_процедура, локальные_имена) {
+                проверить_изменяемость(переменная, имя)?;
                 локация.протолкнуть_смещение(пп, &имя.лок, переменная.смещение);
                 return Ok(переменная.тип.clone());
             }
@@ -1475,7 +2687,8 @@ fn скомпилировать_левое_выражение(пп: &mut ПП, 
             Err(())
         }
         Выражение::ЦепочкаВызовов{имя, цепочка_аргументов} => {
-            let mut тип = if let Some((переменная, локация)) = найти_переменную_по_имени(&имя.текст, имена, текущая_процедура, локальные_имена) {
+            let тип = if let Some((переменная, локация)) = найти_переменную_по_имени(&имя.текст, имена, текущая_процедура, локальные_имена) {
+                проверить_изменяемость(переменная, имя)?;
                 локация.протолкнуть_смещение(пп, &имя.лок, переменная.смещение);
                 переменная.тип.clone()
             } else if let Some(_) = найти_константу_по_имени(&имя.текст, имена, Some(локальные_имена)) {
@@ -1487,36 +2700,7 @@ fn скомпилировать_левое_выражение(пп: &mut ПП, 
                 return Err(())
             };
 
-            for аргументы in цепочка_аргументов.iter() {
-                match &тип {
-                    Тип::Массив{тип_элемента, ..} => {
-                        if аргументы.выражения.len() != 1 {
-                            диагностика!(&имя.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                            return Err(());
-                        };
-                        let индекс = &аргументы.выражения[0];
-                        индексировать_массив(&*тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                        тип = *тип_элемента.clone();
-                    }
-                    Тип::Срез{тип_элемента} => {
-                        if аргументы.выражения.len() != 1 {
-                            диагностика!(&имя.лок, "ОШИБКА", "Некорректная размерность среза. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                            return Err(());
-                        };
-                        let индекс = &аргументы.выражения[0];
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: имя.лок.clone()});
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя.лок.clone()});
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя.лок.clone()});
-                        индексировать_массив(&*тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                        тип = *тип_элемента.clone();
-                    }
-                    _ => {
-                        диагностика!(&имя.лок, "ОШИБКА", "Не возможно индексировать переменные тип «{тип}»", тип = тип.текст());
-                        return Err(());
-                    }
-                }
-            }
-            Ok(тип)
+            скомпилировать_левую_цепочку_индексов(пп, имена, текущая_процедура, локальные_имена, &имя.лок, тип, цепочка_аргументов, временный_размер)
         }
         Выражение::УнарныйМинус{ключ, ..} => {
             диагностика!(&ключ.лок, "ОШИБКА", "Унарный минус не может находится в левой части присваивания");
@@ -1549,7 +2733,7 @@ fn скомпилировать_левое_выражение(пп: &mut ПП, 
                         }
                     }
                     Выражение::ЦепочкаВызовов{имя: имя_поля, цепочка_аргументов} => {
-                        let mut тип = match &левый_тип {
+                        let тип = match &левый_тип {
                             Тип::Структура(имя_структуры) => {
                                 let структура = имена
                                     .структуры
@@ -1570,38 +2754,7 @@ fn скомпилировать_левое_выражение(пп: &mut ПП, 
                             }
                         };
 
-                        for аргументы in цепочка_аргументов.iter() {
-                            match &тип {
-                                Тип::Массив{тип_элемента, ..} => {
-                                    if аргументы.выражения.len() != 1 {
-                                        диагностика!(&имя_поля.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                                        return Err(());
-                                    };
-                                    let индекс = &аргументы.выражения[0];
-                                    индексировать_массив(&*тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                                    тип = *тип_элемента.clone();
-                                }
-                                Тип::Срез{тип_элемента} => {
-                                    if аргументы.выражения.len() != 1 {
-                                        диагностика!(&имя_поля.лок, "ОШИБКА", "Некорректная размерность массива. Ожидался 1 индекс, но повстречалось {ранк}", ранк = аргументы.выражения.len());
-                                        return Err(());
-                                    };
-
-                                    let индекс = &аргументы.выражения[0];
-                                    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: имя_поля.лок.clone()});
-                                    пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: имя_поля.лок.clone()});
-                                    пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: имя_поля.лок.clone()});
-                                    индексировать_массив(&*тип_элемента, пп, имена, текущая_процедура, локальные_имена, индекс, временный_размер)?;
-                                    тип = *тип_элемента.clone();
-                                }
-                                _ => {
-                                    диагностика!(&имя_поля.лок, "ОШИБКА", "Не возможно индексировать переменные тип «{тип}»", тип = тип.текст());
-                                    return Err(());
-                                }
-                            }
-                        }
-
-                        Ok(тип)
+                        скомпилировать_левую_цепочку_индексов(пп, имена, текущая_процедура, локальные_имена, &имя_поля.лок, тип, цепочка_аргументов, временный_размер)
                     }
                     _ => {
                         диагностика!(&правое.лок(), "ОШИБКА", "Данное выражение не может быть использовано как поле");
@@ -1617,6 +2770,42 @@ fn скомпилировать_левое_выражение(пп: &mut ПП, 
             диагностика!(&ключ.лок, "ОШИБКА", "Отрицание не может находится в левой части присваивания");
             Err(())
         }
+        Выражение::Адрес{ключ, ..} => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Взятие адреса не может находится в левой части присваивания");
+            Err(())
+        }
+        Выражение::Пусто(лексема) => {
+            диагностика!(&лексема.лок, "ОШИБКА", "«пусто» не может находится в левой части присваивания");
+            Err(())
+        }
+        Выражение::Разыменование{ключ, выражение} => {
+            скомпилировать_разыменование(пп, имена, текущая_процедура, локальные_имена, ключ, выражение, временный_размер)
+        }
+    }
+}
+
+/// Проталкивает на стек адрес, хранящийся в указателе, и возвращает тип
+/// элемента, на который он указывает.
+fn скомпилировать_разыменование(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &ЛокальныеИмена, ключ: &Лексема, выражение: &Выражение, временный_размер: &mut usize) -> Результат<Тип> {
+    let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, выражение, временный_размер)?;
+    match тип {
+        Тип::Указатель{тип_элемента} => Ok(*тип_элемента),
+        Тип::Пусто => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Разыменование «пусто»");
+            Err(())
+        }
+        _ => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Разыменовать можно только указатель, но повстречался тип «{тип}»", тип = тип.текст());
+            Err(())
+        }
+    }
+}
+
+fn является_левым_выражением(выражение: &Выражение) -> bool {
+    match выражение {
+        Выражение::Идент(..) | Выражение::ЦепочкаВызовов{..} | Выражение::Разыменование{..} => true,
+        Выражение::Биноп{вид: ВидБинопа::Поле, ..} => true,
+        _ => false,
     }
 }
 
@@ -1630,13 +2819,34 @@ fn записать_тип(лок: &Лок, код: &mut Vec<Инструкци
     Ok(())
 }
 
+/// Ошибка в одном утверждении не мешает проверить остальные утверждения
+/// блока. Области видимости и циклы, которые упавшее утверждение не успело
+/// закрыть, просто выбрасываются, т.к. код с ошибками всё равно никто не
+/// будет исполнять.
+fn скомпилировать_блок(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &mut ЛокальныеИмена, блок: &[Утверждение], отложенные_возвраты: &mut Vec<usize>) -> Результат<()> {
+    let mut результат = Ok(());
+    for утверждение in блок {
+        let количество_областей = локальные_имена.стек_областей.len();
+        let количество_циклов = локальные_имена.стек_циклов.len();
+        if let Err(()) = скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты) {
+            if слишком_много_ошибок() {
+                return Err(());
+            }
+            локальные_имена.стек_областей.truncate(количество_областей);
+            локальные_имена.стек_циклов.truncate(количество_циклов);
+            результат = Err(());
+        }
+    }
+    результат
+}
+
 fn скомпилировать_утверждение(пп: &mut ПП, имена: &Имена, текущая_процедура: &СкомпПроцедура, локальные_имена: &mut ЛокальныеИмена, утверждение: &Утверждение, отложенные_возвраты: &mut Vec<usize>) -> Результат<()> {
     match утверждение {
         Утверждение::Присваивание{ключ, левое, правое} => {
             let mut временный_размер = 0;
             let правый_тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, правое, &mut временный_размер)?;
             let левый_тип = скомпилировать_левое_выражение(пп, имена, текущая_процедура, локальные_имена, левое, &mut временный_размер)?;
-            проверить_типы(правое.лок(), &левый_тип, &правый_тип)?;
+            проверить_тип_значения(пп, правое, &левый_тип, правый_тип)?;
             записать_тип(&ключ.лок, &mut пп.код, &имена.структуры, &левый_тип)?;
             if временный_размер > 0 {
                 пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: ключ.лок.clone() })
@@ -1644,7 +2854,8 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             Ok(())
         }
         Утверждение::Выражение{выражение} => {
-            match выражение {
+            let имя_модуля = разрешить_имя_модуля(имена, текущая_процедура, локальные_имена, выражение);
+            match имя_модуля.as_ref().unwrap_or(выражение) {
                 Выражение::ЦепочкаВызовов{имя, цепочка_аргументов} => {
                     match &цепочка_аргументов.as_slice() {
                         &[] => unreachable!("Цепочка вызовов должна содержать как минимум один набор аргументов. Если это не так, то это баг разбора кода"),
@@ -1676,9 +2887,8 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             пп.код.push(Инструкция{ вид: ВидИнструкции::Ноп, лок: ключ.лок.clone() });
 
             локальные_имена.стек_областей.push(Default::default());
-            for утверждение in тело.iter() {
-                скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты)?;
-            }
+            // Ветку «иначе» проверяем даже если в теле были ошибки.
+            let тело_скомпилировано = скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &тело, отложенные_возвраты);
             let размер_области = локальные_имена
                 .стек_областей
                 .pop()
@@ -1700,9 +2910,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 пп.код[точка_условного_прыжка].вид = ВидИнструкции::УсловныйПрыжок(точка_иначе);
 
                 локальные_имена.стек_областей.push(Default::default());
-                for утверждение in иначе.iter() {
-                    скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты)?;
-                }
+                скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &иначе, отложенные_возвраты)?;
                 let размер_области = локальные_имена
                     .стек_областей
                     .pop()
@@ -1715,23 +2923,18 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 let точка_выхода = пп.код.len();
                 пп.код[точка_конца_тела].вид = ВидИнструкции::Прыжок(точка_выхода);
             }
-            Ok(())
+            тело_скомпилировано
         }
         Утверждение::Вернуть{ключ, выражение} => {
             if let Some(выражение) = выражение {
                 if let Some(результат) = &текущая_процедура.результат {
                     let mut временный_размер = 0;
                     let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &выражение, &mut временный_размер)?;
-                    проверить_типы(&выражение.лок(), &результат.тип, &тип)?;
+                    проверить_тип_значения(пп, &выражение, &результат.тип, тип)?;
                     пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(результат.смещение_от_кадра), лок: ключ.лок.clone() });
-                    записать_тип(&ключ.лок, &mut пп.код, &имена.структуры, &тип)?;
-                    let размер_всех_локальных_переменных = локальные_имена
-                        .стек_областей
-                        .iter()
-                        .map(|область| область.размер_переменных_на_стеке)
-                        .sum::<usize>();
-                    if временный_размер + размер_всех_локальных_переменных > 0 {
-                        пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека((временный_размер + размер_всех_локальных_переменных) as u64), лок: выражение.лок().clone() });
+                    записать_тип(&ключ.лок, &mut пп.код, &имена.структуры, &результат.тип)?;
+                    if временный_размер > 0 {
+                        пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: выражение.лок().clone() });
                     }
                 } else {
                     диагностика!(выражение.лок(), "ОШИБКА", "Процедура не возвращает никаких значений");
@@ -1743,11 +2946,19 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                     return Err(());
                 }
             }
+            let размер_всех_локальных_переменных = локальные_имена
+                .стек_областей
+                .iter()
+                .map(|область| область.размер_переменных_на_стеке)
+                .sum::<usize>();
+            if размер_всех_локальных_переменных > 0 {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_всех_локальных_переменных as u64), лок: ключ.лок.clone() });
+            }
             отложенные_возвраты.push(пп.код.len());
             пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(0), лок: ключ.лок.clone() });
             Ok(())
         }
-        Утверждение::Пока{ключ, условие, тело} => {
+        Утверждение::Пока{ключ, метка, условие, тело} => {
             let точка_условия = пп.код.len();
             let mut временный_размер = 0;
             let тип = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &условие, &mut временный_размер)?;
@@ -1759,10 +2970,9 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             let точка_условного_прыжка = пп.код.len();
 
             пп.код.push(Инструкция{ вид: ВидИнструкции::Ноп, лок: ключ.лок.clone() });
+            локальные_имена.начать_цикл(метка)?;
             локальные_имена.стек_областей.push(Default::default());
-            for утверждение in тело.iter() {
-                скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты)?;
-            }
+            скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &тело, отложенные_возвраты)?;
             let размер_области = локальные_имена
                 .стек_областей
                 .pop()
@@ -1775,9 +2985,10 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(точка_условия), лок: ключ.лок.clone() });
             let точка_выхода = пп.код.len();
             пп.код[точка_условного_прыжка].вид = ВидИнструкции::УсловныйПрыжок(точка_выхода);
+            локальные_имена.закончить_цикл(пп, точка_условия, точка_выхода);
             Ok(())
         }
-        Утверждение::Для{ключ, индекс, нижняя_граница, верхняя_граница, тело} => {
+        Утверждение::Для{ключ, метка, индекс, нижняя_граница, верхняя_граница, тело} => {
             локальные_имена.стек_областей.push(Default::default());
 
             let размер_всех_локальных_переменных = локальные_имена
@@ -1808,7 +3019,10 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             текущая_область_видимости.переменные.insert(индекс.текст.clone(), СкомпПеременная {
                 имя: индекс.clone(),
                 тип: тип_переменной,
-                смещение
+                смещение,
+                ссылка: false,
+                в_иниц_данных: false,
+                только_чтение: false,
             });
             текущая_область_видимости.размер_переменных_на_стеке += размер;
 
@@ -1818,7 +3032,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 // Инициализация индексной переменной
                 let mut временный_размер = 0;
                 let тип_нижней_границы = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &нижняя_граница, &mut временный_размер)?;
-                проверить_типы(&нижняя_граница.лок(), &переменная.тип, &тип_нижней_границы)?;
+                проверить_тип_значения(пп, &нижняя_граница, &переменная.тип, тип_нижней_границы)?;
                 локация.протолкнуть_смещение(пп, &индекс.лок, переменная.смещение);
                 записать_тип(&индекс.лок, &mut пп.код, &имена.структуры, &переменная.тип)?;
                 if временный_размер > 0 {
@@ -1831,7 +3045,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 локация.протолкнуть_смещение(пп, &индекс.лок, переменная.смещение);
                 прочитать_примитивный_тип(&индекс.лок, &mut пп.код, &имена.структуры, &переменная.тип)?;
                 let тип_верхней_границы = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &верхняя_граница, &mut временный_размер)?;
-                проверить_типы(&верхняя_граница.лок(), &переменная.тип, &тип_верхней_границы)?;
+                проверить_тип_значения(пп, &верхняя_граница, &переменная.тип, тип_верхней_границы)?;
                 пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелБольше, лок: ключ.лок.clone() });
                 let точка_условного_прыжка = пп.код.len();
                 пп.код.push(Инструкция{ вид: ВидИнструкции::Ноп, лок: ключ.лок.clone() });
@@ -1843,10 +3057,9 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 let переменная_смещение = переменная.смещение;
 
                 // Тело
+                локальные_имена.начать_цикл(метка)?;
                 локальные_имена.стек_областей.push(Default::default());
-                for утверждение in тело.iter() {
-                    скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты)?;
-                }
+                скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &тело, отложенные_возвраты)?;
                 let размер_области = локальные_имена
                     .стек_областей
                     .pop()
@@ -1857,6 +3070,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 }
 
                 // Инкремент
+                let точка_инкремента = пп.код.len();
                 локация.протолкнуть_смещение(пп, &индекс.лок, переменная_смещение);
                 прочитать_примитивный_тип(&индекс.лок, &mut пп.код, &имена.структуры, &переменная_тип)?;
                 пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(1), лок: ключ.лок.clone() });
@@ -1868,6 +3082,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(точка_условия), лок: ключ.лок.clone() });
                 let точка_выхода = пп.код.len();
                 пп.код[точка_условного_прыжка].вид = ВидИнструкции::УсловныйПрыжок(точка_выхода);
+                локальные_имена.закончить_цикл(пп, точка_инкремента, точка_выхода);
 
                 let размер_области = локальные_имена
                     .стек_областей
@@ -1884,6 +3099,155 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             диагностика!(&индекс.лок, "ОШИБКА", "Неизвестная переменная «{имя}»", имя = индекс.текст);
             Err(())
         }
+        Утверждение::ДляКаждого{ключ, метка, индекс, элемент, коллекция, тело} => {
+            локальные_имена.стек_областей.push(Default::default());
+
+            // Адрес и количество элементов вычисляются один раз до начала
+            // цикла и хранятся в скрытых переменных.
+            let смещение_адреса = выделить_переменную_цикла(пп, локальные_имена, &ключ.лок);
+            let смещение_количества = выделить_переменную_цикла(пп, локальные_имена, &ключ.лок);
+            let смещение_индекса = выделить_переменную_цикла(пп, локальные_имена, &ключ.лок);
+            let смещение_элемента = выделить_переменную_цикла(пп, локальные_имена, &ключ.лок);
+
+            // Строковые литералы и константы лежат в общих данных программы,
+            // так что их элементы можно только читать.
+            let только_чтение = match коллекция {
+                Выражение::Строка(..) | Выражение::ЦепочкаСтроки{..} => true,
+                Выражение::Идент(имя) | Выражение::ЦепочкаВызовов{имя, ..} => {
+                    найти_переменную_по_имени(&имя.текст, имена, текущая_процедура, локальные_имена).is_none() &&
+                        найти_константу_по_имени(&имя.текст, имена, Some(локальные_имена)).is_some()
+                }
+                _ => false,
+            };
+
+            let mut временный_размер = 0;
+            let тип_коллекции = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, коллекция, &mut временный_размер)?;
+            let тип_элемента = match &тип_коллекции {
+                Тип::Массив{тип_элемента, размер} => {
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_адреса), лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(*размер), лок: ключ.лок.clone() });
+                    *тип_элемента.clone()
+                }
+                Тип::Срез{тип_элемента} => {
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_АДРЕС_СМЕЩЕНИЕ), лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_адреса), лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ), лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: ключ.лок.clone() });
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+                    *тип_элемента.clone()
+                }
+                _ => {
+                    диагностика!(коллекция.лок(), "ОШИБКА", "Обходить в цикле «для» можно только массивы и срезы, но повстречался тип «{тип}»", тип = тип_коллекции.текст());
+                    return Err(());
+                }
+            };
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_количества), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: ключ.лок.clone() });
+            // Временные данные коллекции (например, массив, возвращённый
+            // процедурой) должны дожить до конца цикла, поэтому
+            // освобождаются вместе с переменными цикла.
+            локальные_имена
+                .стек_областей
+                .last_mut()
+                .expect("Мы только что протолкнули область цикла")
+                .размер_переменных_на_стеке += временный_размер;
+
+            let текущая_область_видимости = локальные_имена
+                .стек_областей
+                .last_mut()
+                .expect("Мы только что протолкнули область цикла");
+            if let Some(индекс) = индекс {
+                if индекс.текст == элемент.текст {
+                    диагностика!(&элемент.лок, "ОШИБКА", "Переопределение локальной переменной «{имя}»", имя = элемент.текст);
+                    диагностика!(&индекс.лок, "ИНФО", "Первое определение находится здесь");
+                    return Err(());
+                }
+                текущая_область_видимости.переменные.insert(индекс.текст.clone(), СкомпПеременная {
+                    имя: индекс.clone(),
+                    тип: Тип::Цел64,
+                    смещение: смещение_индекса,
+                    ссылка: false,
+                    в_иниц_данных: false,
+                    только_чтение: false,
+                });
+            }
+            текущая_область_видимости.переменные.insert(элемент.текст.clone(), СкомпПеременная {
+                имя: элемент.clone(),
+                тип: тип_элемента.clone(),
+                смещение: смещение_элемента,
+                ссылка: true,
+                в_иниц_данных: false,
+                только_чтение,
+            });
+
+            // Инициализация индекса
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Целое(0), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_индекса), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: ключ.лок.clone() });
+
+            // Проверка условия выхода из цикла
+            let точка_условия = пп.код.len();
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_индекса), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_количества), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ЦелБольшеРавно, лок: ключ.лок.clone() });
+            let точка_условного_прыжка = пп.код.len();
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Ноп, лок: ключ.лок.clone() });
+
+            // Элемент ссылается на текущий элемент коллекции
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_адреса), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_индекса), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(тип_элемента.размер(&имена.структуры)), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатУмножение, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_элемента), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: ключ.лок.clone() });
+
+            // Тело
+            локальные_имена.начать_цикл(метка)?;
+            локальные_имена.стек_областей.push(Default::default());
+            скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &тело, отложенные_возвраты)?;
+            let размер_области = локальные_имена
+                .стек_областей
+                .pop()
+                .expect("Тело цикла «для» не трогает область которую мы протолкнули")
+                .размер_переменных_на_стеке;
+            if размер_области > 0 {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_области as u64), лок: ключ.лок.clone() });
+            }
+
+            // Инкремент
+            let точка_инкремента = пп.код.len();
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_индекса), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прочитать64, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Натуральное(1), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::НатСложение, лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение_индекса), лок: ключ.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: ключ.лок.clone() });
+
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(точка_условия), лок: ключ.лок.clone() });
+            let точка_выхода = пп.код.len();
+            пп.код[точка_условного_прыжка].вид = ВидИнструкции::УсловныйПрыжок(точка_выхода);
+            локальные_имена.закончить_цикл(пп, точка_инкремента, точка_выхода);
+
+            let размер_области = локальные_имена
+                .стек_областей
+                .pop()
+                .expect("Тело цикла «для» не трогает область которую мы протолкнули")
+                .размер_переменных_на_стеке;
+            if размер_области > 0 {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_области as u64), лок: ключ.лок.clone() });
+            }
+            Ok(())
+        }
         Утверждение::ДекларацияПеременной{ключ, имя, тип, значение} => {
             let размер_всех_локальных_переменных = локальные_имена
                 .стек_областей
@@ -1891,23 +3255,53 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 .map(|область| область.размер_переменных_на_стеке)
                 .sum::<usize>();
 
-            let тип_переменной = скомпилировать_тип(пп, тип, имена, Some(локальные_имена))?;
+            let явный_тип = match тип {
+                Some(тип) => Some(скомпилировать_тип(пп, тип, имена, Some(локальные_имена))?),
+                None => None,
+            };
+            // Если тип не указан, то размер переменной станет известен только
+            // после компиляции начального значения.
+            let выделение = пп.код.len();
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(0), лок: ключ.лок.clone() });
+
+            let mut временный_размер = 0;
+            let значение_и_тип = значение.as_ref().map(|значение| {
+                (значение, скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, значение, &mut временный_размер))
+            });
+            // Даже если инициализация не скомпилировалась, переменную с
+            // явным типом всё равно объявляем, чтобы не сыпать ошибками о
+            // неизвестном имени в остальных утверждениях.
+            let (тип_переменной, mut инициализация) = match (явный_тип, значение_и_тип) {
+                (Some(тип), Some((значение, Ok(тип_значения)))) => {
+                    let проверка = проверить_тип_значения(пп, значение, &тип, тип_значения);
+                    (тип, проверка)
+                }
+                (Some(тип), Some((_, Err(())))) => (тип, Err(())),
+                (Some(тип), None) => (тип, Ok(())),
+                (None, Some((значение, Ok(тип_значения)))) => {
+                    проверить_выведенный_тип(значение, &тип_значения)?;
+                    (тип_значения, Ok(()))
+                }
+                (None, Some((_, Err(())))) => return Err(()),
+                (None, None) => unreachable!("У переменной без типа всегда есть начальное значение. Это проверяется на этапе разбора"),
+            };
+
             let размер = выравнять_размер_по_слову(тип_переменной.размер(&имена.структуры));
-            пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(размер as u64), лок: ключ.лок.clone() });
+            пп.код[выделение].вид = ВидИнструкции::ВыделитьНаСтеке(размер as u64);
             let смещение =
                 -(2*(РАЗМЕР_СЛОВА as i32))                  // пропускаем кадр и адрес возврата
                 -(размер as i32)                            // сама переменная
                 -(размер_всех_локальных_переменных as i32); // пропускаем все предыдущие локальные переменные
 
-            if let Some(значение) = значение {
-                let mut временный_размер = 0;
-                let тип_значения = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, значение, &mut временный_размер)?;
-                проверить_типы(&значение.лок(), &тип_переменной, &тип_значения)?;
-                пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение), лок: ключ.лок.clone() });
-                записать_тип(&ключ.лок, &mut пп.код, &имена.структуры, &тип_переменной)?;
-                if временный_размер > 0 {
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: ключ.лок.clone() });
-                }
+            if значение.is_some() && инициализация.is_ok() {
+                инициализация = (|| {
+                    пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(смещение), лок: ключ.лок.clone() });
+                    записать_тип(&ключ.лок, &mut пп.код, &имена.структуры, &тип_переменной)?;
+                    if временный_размер > 0 {
+                        пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: ключ.лок.clone() });
+                    }
+                    Ok(())
+                })();
             }
 
             let текущая_область_видимости = локальные_имена
@@ -1923,14 +3317,18 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
             текущая_область_видимости.переменные.insert(имя.текст.clone(), СкомпПеременная {
                 имя: имя.clone(),
                 тип: тип_переменной,
-                смещение
+                смещение,
+                ссылка: false,
+                в_иниц_данных: false,
+                только_чтение: false,
             });
             текущая_область_видимости.размер_переменных_на_стеке += размер;
 
-            Ok(())
+            инициализация
         }
         Утверждение::ДекларацияКонстанты{имя, значение, ..} => {
-            let (байты_значения, тип) = скомпилировать_константное_выражение(пп, имена, Some(локальные_имена), значение)?;
+            проверить_литерал_по_умолчанию(значение, &Тип::Цел64)?;
+            let значение = скомпилировать_константное_выражение(пп, имена, Some(локальные_имена), значение)?;
 
             let текущая_область_видимости = локальные_имена
                 .стек_областей
@@ -1943,53 +3341,146 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 return Err(());
             }
 
-            if тип.примитивный() {
-                let значение = u64::from_le_bytes(байты_значения.as_slice().try_into().unwrap());
-                текущая_область_видимости.константы.insert(имя.текст.clone(), СкомпКонстанта {
-                    имя: имя.clone(),
-                    тип,
-                    значение_или_адрес: значение,
-                });
-            } else {
-                let адрес = пп.иниц_данные.len();
-                пп.иниц_данные.extend(байты_значения);
+            текущая_область_видимости.константы.insert(имя.текст.clone(), разместить_константу(пп, имя.clone(), None, значение));
 
-                текущая_область_видимости.константы.insert(имя.текст.clone(), СкомпКонстанта {
-                    имя: имя.clone(),
-                    тип,
-                    значение_или_адрес: адрес as u64,
-                });
+            Ok(())
+        }
+        Утверждение::Прервать{ключ, метка} | Утверждение::Продолжить{ключ, метка} => {
+            let индекс_цикла = if let Some(метка) = метка {
+                if let Some(индекс) = локальные_имена.стек_циклов.iter().rposition(|цикл| цикл.метка.as_ref().map(|м| м.текст == метка.текст).unwrap_or(false)) {
+                    индекс
+                } else {
+                    диагностика!(&метка.лок, "ОШИБКА", "Неизвестная метка цикла «{имя}»", имя = метка.текст);
+                    return Err(());
+                }
+            } else if let Some(индекс) = локальные_имена.стек_циклов.len().checked_sub(1) {
+                индекс
+            } else {
+                диагностика!(&ключ.лок, "ОШИБКА", "{ключ} вне цикла", ключ = ключ.вид.сущ().текст);
+                return Err(());
+            };
+            let глубина_областей = локальные_имена.стек_циклов[индекс_цикла].глубина_областей;
+            let размер_областей = локальные_имена.стек_областей[глубина_областей..]
+                .iter()
+                .map(|область| область.размер_переменных_на_стеке)
+                .sum::<usize>();
+            if размер_областей > 0 {
+                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_областей as u64), лок: ключ.лок.clone() });
+            }
+            let точка_прыжка = пп.код.len();
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(0), лок: ключ.лок.clone() });
+            let цикл = &mut локальные_имена.стек_циклов[индекс_цикла];
+            match утверждение {
+                Утверждение::Прервать{..} => цикл.прыжки_на_выход.push(точка_прыжка),
+                _ => цикл.прыжки_на_продолжение.push(точка_прыжка),
             }
-
             Ok(())
         }
         Утверждение::Вилка{ключ, выражение, ветки, любое} => {
             let mut временный_размер_корня: usize = 0;
             let тип_корня = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, выражение, &mut временный_размер_корня)?;
-            if !тип_корня.примитивный() {
-                // СДЕЛАТЬ: вилка для сложных типов
-                диагностика!(выражение.лок(), "ОШИБКА", "Использовать вилку пока что можно только для примитивных типов");
-                return Err(());
+            проверить_образцы_вилки(имена, локальные_имена, &тип_корня, ветки)?;
+            if let (Тип::Перечисление(имя_перечисления), None) = (&тип_корня, любое) {
+                let перечисление = имена
+                    .перечисления
+                    .get(имя_перечисления)
+                    .expect("Существование перечисления должно быть уже проверено на этапе компиляции типа");
+                let mut покрытые = vec![false; перечисление.значения.len()];
+                for образец in ветки.iter().flat_map(|ветка| ветка.образцы.iter()) {
+                    let ОбразецВилки::Значение(выражение) = образец else {
+                        continue;
+                    };
+                    let значение = match выражение {
+                        Выражение::Биноп{вид: ВидБинопа::Поле, левое, правое, ..} => значение_перечисления(имена, левое, правое)?.map(|(_, значение)| значение),
+                        Выражение::Идент(имя) => найти_константу_по_имени(&имя.текст, имена, Some(локальные_имена))
+                            .filter(|константа| константа.тип == тип_корня)
+                            .map(|константа| константа.значение_или_адрес as usize),
+                        _ => None,
+                    };
+                    if let Some(значение) = значение {
+                        покрытые[значение] = true;
+                    }
+                }
+                let непокрытые: Vec<&str> = перечисление
+                    .значения
+                    .iter()
+                    .zip(покрытые.iter())
+                    .filter(|(_, покрыто)| !**покрыто)
+                    .map(|(значение, _)| значение.текст.as_str())
+                    .collect();
+                if !непокрытые.is_empty() {
+                    диагностика!(&ключ.лок, "ОШИБКА", "Вилка по перечислению «{перечисление}» не покрывает {значения}: {непокрытые}",
+                                 перечисление = имя_перечисления,
+                                 значения = if непокрытые.len() == 1 { "значение" } else { "значения" },
+                                 непокрытые = непокрытые.join(", "));
+                    диагностика!(&перечисление.имя.лок, "ИНФО", "Перечисление определено здесь. Добавьте недостающие ветки или «когда любое».");
+                    return Err(());
+                }
             }
             let mut прыжки_в_конец = Vec::new();
             for ветка in ветки {
-                пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: выражение.лок().clone() });
-                let mut временный_размер_ветки: usize = 0;
-                let тип_ветки = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, &ветка.выражение, &mut временный_размер_ветки)?;
-                проверить_типы(ветка.выражение.лок(), &тип_корня, &тип_ветки)?;
-                пп.код.push(Инструкция{ вид: ВидИнструкции::НатРавно, лок: ветка.ключ.лок.clone() });
-                пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ветка.ключ.лок.clone() });
-                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер_ветки as u64), лок: ветка.ключ.лок.clone() });
+                let mut прыжки_в_тело = Vec::new();
+                for образец in &ветка.образцы {
+                    match образец {
+                        ОбразецВилки::Значение(значение) => {
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: выражение.лок().clone() });
+                            let mut временный_размер_образца: usize = 0;
+                            скомпилировать_равенство_образца(пп, имена, текущая_процедура, локальные_имена, &ветка.ключ, &тип_корня, значение, &mut временный_размер_образца)?;
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер_образца as u64), лок: ветка.ключ.лок.clone() });
+                            прыжки_в_тело.push(пп.код.len());
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::УсловныйПрыжок(0), лок: ветка.ключ.лок.clone() });
+                        }
+                        ОбразецВилки::Диапазон{от, ключ: ключ_диапазона, до} => {
+                            let (Some(больше_равно), Some(меньше_равно)) = (инструкция_сравнения(&тип_корня, &ВидБинопа::БольшеРавно), инструкция_сравнения(&тип_корня, &ВидБинопа::МеньшеРавно)) else {
+                                диагностика!(&ключ_диапазона.лок, "ОШИБКА", "Диапазоны в вилке недопустимы для типа «{тип}», т.к. его значения не упорядочены",
+                                             тип = тип_корня.текст());
+                                return Err(());
+                            };
+
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: выражение.лок().clone() });
+                            let mut временный_размер_образца: usize = 0;
+                            let тип_от = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, от, &mut временный_размер_образца)?;
+                            проверить_тип_значения(пп, от, &тип_корня, тип_от)?;
+                            пп.код.push(Инструкция{ вид: больше_равно, лок: ключ_диапазона.лок.clone() });
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::ЛогОтрицание, лок: ключ_диапазона.лок.clone() });
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер_образца as u64), лок: ключ_диапазона.лок.clone() });
+                            let прыжок_мимо_диапазона = пп.код.len();
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::УсловныйПрыжок(0), лок: ключ_диапазона.лок.clone() });
+
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::Продублировать, лок: выражение.лок().clone() });
+                            let mut временный_размер_образца: usize = 0;
+                            let тип_до = скомпилировать_выражение(пп, имена, текущая_процедура, локальные_имена, до, &mut временный_размер_образца)?;
+                            проверить_тип_значения(пп, до, &тип_корня, тип_до)?;
+                            пп.код.push(Инструкция{ вид: меньше_равно, лок: ключ_диапазона.лок.clone() });
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер_образца as u64), лок: ключ_диапазона.лок.clone() });
+                            прыжки_в_тело.push(пп.код.len());
+                            пп.код.push(Инструкция{ вид: ВидИнструкции::УсловныйПрыжок(0), лок: ключ_диапазона.лок.clone() });
+
+                            let следующий_образец = пп.код.len();
+                            if let ВидИнструкции::УсловныйПрыжок(адрес) = &mut пп.код[прыжок_мимо_диапазона].вид {
+                                *адрес = следующий_образец;
+                            } else {
+                                unreachable!();
+                            }
+                        }
+                    }
+                }
                 let прыжок_на_следующую_ветку = пп.код.len();
-                пп.код.push(Инструкция{ вид: ВидИнструкции::УсловныйПрыжок(0), лок: ветка.ключ.лок.clone() });
+                пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(0), лок: ветка.ключ.лок.clone() });
 
+                let тело_ветки = пп.код.len();
+                for прыжок in прыжки_в_тело {
+                    if let ВидИнструкции::УсловныйПрыжок(адрес) = &mut пп.код[прыжок].вид {
+                        *адрес = тело_ветки;
+                    } else {
+                        unreachable!();
+                    }
+                }
                 пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер_корня as u64), лок: ветка.ключ.лок.clone() });
                 пп.код.push(Инструкция{ вид: ВидИнструкции::Вытолкнуть, лок: ветка.ключ.лок.clone() });
 
                 локальные_имена.стек_областей.push(Default::default());
-                for утверждение in ветка.тело.iter() {
-                    скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты)?;
-                }
+                скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &ветка.тело, отложенные_возвраты)?;
                 let размер_области = локальные_имена
                     .стек_областей
                     .pop()
@@ -2003,7 +3494,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
                 пп.код.push(Инструкция{ вид: ВидИнструкции::Прыжок(0), лок: ветка.ключ.лок.clone() });
 
                 let следующая_ветка = пп.код.len();
-                if let ВидИнструкции::УсловныйПрыжок(адрес) = &mut пп.код[прыжок_на_следующую_ветку].вид {
+                if let ВидИнструкции::Прыжок(адрес) = &mut пп.код[прыжок_на_следующую_ветку].вид {
                     *адрес = следующая_ветка;
                 } else {
                     unreachable!();
@@ -2014,9 +3505,7 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
 
             if let Some((любое_ключ, любое_тело)) = любое {
                 локальные_имена.стек_областей.push(Default::default());
-                for утверждение in любое_тело.iter() {
-                    скомпилировать_утверждение(пп, имена, текущая_процедура, локальные_имена, утверждение, отложенные_возвраты)?;
-                }
+                скомпилировать_блок(пп, имена, текущая_процедура, локальные_имена, &любое_тело, отложенные_возвраты)?;
                 let размер_области = локальные_имена
                     .стек_областей
                     .pop()
@@ -2041,6 +3530,25 @@ fn скомпилировать_утверждение(пп: &mut ПП, имен
     }
 }
 
+/// Выделить на стеке слово под переменную цикла в текущей области
+/// видимости и вернуть её смещение от кадра.
+fn выделить_переменную_цикла(пп: &mut ПП, локальные_имена: &mut ЛокальныеИмена, лок: &Лок) -> i32 {
+    let размер_всех_локальных_переменных = локальные_имена
+        .стек_областей
+        .iter()
+        .map(|область| область.размер_переменных_на_стеке)
+        .sum::<usize>();
+    пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(РАЗМЕР_СЛОВА as u64), лок: лок.clone() });
+    локальные_имена
+        .стек_областей
+        .last_mut()
+        .expect("Компиляция процедуры обязана создать хотя бы одну область видимости")
+        .размер_переменных_на_стеке += РАЗМЕР_СЛОВА;
+    -(2*(РАЗМЕР_СЛОВА as i32))                  // пропускаем кадр и адрес возврата
+    -(РАЗМЕР_СЛОВА as i32)                      // сама переменная
+    -(размер_всех_локальных_переменных as i32)  // пропускаем все предыдущие локальные переменные
+}
+
 fn выравнять_размер_по_слову(размер: usize) -> usize {
     (размер + РАЗМЕР_СЛОВА - 1)/РАЗМЕР_СЛОВА*РАЗМЕР_СЛОВА
 }
@@ -2083,7 +3591,9 @@ fn прочитать_примитивный_тип(лок: &Лок, код: &mu
 }
 
 
-fn скомпилировать_процедуру(пп: &mut ПП, имена: &mut Имена, процедура: Процедура) -> Результат<()> {
+/// Скомпилировать сигнатуру процедуры и добавить её в имена. Возвращает
+/// размер параметров и результата процедуры на втором стеке.
+fn зарегистрировать_процедуру(пп: &mut ПП, имена: &mut Имена, процедура: &Процедура, модуль: Option<String>, скрытая: bool) -> Результат<usize> {
     let mut параметры = Vec::new();
     let mut размер_параметров_на_стеке: usize = 0;
 
@@ -2093,13 +3603,20 @@ fn скомпилировать_процедуру(пп: &mut ПП, имена:
             имя: параметр.имя.clone(),
             тип,
             смещение: размер_параметров_на_стеке as i32,
+            ссылка: параметр.ссылка,
+            в_иниц_данных: false,
+            только_чтение: false,
         };
-        размер_параметров_на_стеке += выравнять_размер_по_слову(скомп_параметр.тип.размер(&имена.структуры));
+        if скомп_параметр.ссылка {
+            размер_параметров_на_стеке += РАЗМЕР_СЛОВА;
+        } else {
+            размер_параметров_на_стеке += выравнять_размер_по_слову(скомп_параметр.тип.размер(&имена.структуры));
+        }
         параметры.push(скомп_параметр);
     }
-    let результат = if let Some(тип) = процедура.тип_результата {
+    let результат = if let Some(тип) = &процедура.тип_результата {
         let результат = РезультатСкомпПроцедуры {
-            тип: скомпилировать_тип(пп, &тип, имена, None)?,
+            тип: скомпилировать_тип(пп, тип, имена, None)?,
             смещение_от_кадра: размер_параметров_на_стеке as i32,
         };
         размер_параметров_на_стеке += выравнять_размер_по_слову(результат.тип.размер(&имена.структуры));
@@ -2107,178 +3624,412 @@ fn скомпилировать_процедуру(пп: &mut ПП, имена:
     } else {
         None
     };
-    match процедура.тело {
-        ТелоПроцедуры::Внутренее{блок} => {
-            let адрес = пп.код.len();
-            let скомп_процедура = СкомпПроцедура{
-                имя: процедура.имя.clone(),
-                параметры,
-                точка_входа: ТочкаВхода::Внутреняя{адрес},
-                результат,
-            };
-
-            if let Some(_) = имена.процедуры.insert(скомп_процедура.имя.текст.clone(), скомп_процедура) {
-                unreachable!("Проверка переопределения процедур должна происходить на этапе разбора")
-            }
+    let точка_входа = match &процедура.тело {
+        // Настоящий адрес станет известен при компиляции тела процедуры.
+        ТелоПроцедуры::Внутренее{..} => ТочкаВхода::Внутреняя{адрес: АДРЕС_НЕИЗВЕСТЕН},
+        ТелоПроцедуры::Внешнее{символ} => {
+            let индекс = пп.внешние_символы.len();
+            let индекс = *пп.внешние_символы.entry(символ.текст.clone()).or_insert(индекс);
+            ТочкаВхода::Внешняя{индекс}
+        }
+    };
+    let скомп_процедура = СкомпПроцедура{
+        имя: процедура.имя.clone(),
+        параметры,
+        точка_входа,
+        результат,
+        модуль,
+        скрытая,
+    };
+    if let Some(_) = имена.процедуры.insert(скомп_процедура.ключ(), скомп_процедура) {
+        unreachable!("Проверка переопределения процедур должна происходить на этапе разбора")
+    }
+    Ok(размер_параметров_на_стеке)
+}
 
-            let скомп_процедура = имена.процедуры.get(&процедура.имя.текст).unwrap();
+/// Процедура уже должна быть зарегистрирована через зарегистрировать_процедуру.
+fn скомпилировать_процедуру(пп: &mut ПП, имена: &mut Имена, процедура: &Процедура, ключ: &str, размер_параметров_на_стеке: usize) -> Результат<()> {
+    let ТелоПроцедуры::Внутренее{блок, конец} = &процедура.тело else {
+        return Ok(());
+    };
+    let скомп_процедура = имена.процедуры.get_mut(ключ).expect("Процедура должна быть зарегистрирована");
+    скомп_процедура.точка_входа = ТочкаВхода::Внутреняя{адрес: пп.код.len()};
+    let скомп_процедура = имена.процедуры.get(ключ).unwrap();
+
+    // СДЕЛАТЬ: Если размер_параметров_на_стеке равен 0, то, наверное, не имеет никакого смысла вообще включать данную инструкцию
+    // Тоже самое можно сказать и про противоположную инструкцию ОсвободитьСоВторогоСтека.
+    пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(размер_параметров_на_стеке as u64), лок: процедура.имя.лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::СохранитьКадр, лок: процедура.имя.лок.clone() });
+    пп.код.push(Инструкция{ вид: ВидИнструкции::АргументНаСтек, лок: процедура.имя.лок.clone() }); // сохраняем адрес возврата на второй стек
+    for параметр in скомп_процедура.параметры.iter() {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(параметр.смещение), лок: параметр.имя.лок.clone() });
+        if параметр.ссылка {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::Записать64, лок: параметр.имя.лок.clone() });
+        } else {
+            записать_тип(&параметр.имя.лок, &mut пп.код, &имена.структуры, &параметр.тип)?;
+        }
+    }
+    let mut отложенные_возвраты = Vec::new();
+    let mut локальные_имена = ЛокальныеИмена::default();
+    локальные_имена.стек_областей.push(Default::default());
+    скомпилировать_блок(пп, имена, &скомп_процедура, &mut локальные_имена, блок, &mut отложенные_возвраты)?;
+    let размер_области = локальные_имена
+        .стек_областей
+        .pop()
+        .expect("Тело процедуры не трогает область которую мы протолкнули")
+        .размер_переменных_на_стеке;
+    if размер_области > 0 {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_области as u64), лок: процедура.имя.лок.clone() });
+    }
 
-            // СДЕЛАТЬ: Если размер_параметров_на_стеке равен 0, то, наверное, не имеет никакого смысла вообще включать данную инструкцию
-            // Тоже самое можно сказать и про противоположную инструкцию ОсвободитьСоВторогоСтека.
-            пп.код.push(Инструкция{ вид: ВидИнструкции::ВыделитьНаСтеке(размер_параметров_на_стеке as u64), лок: процедура.имя.лок.clone() });
-            пп.код.push(Инструкция{ вид: ВидИнструкции::СохранитьКадр, лок: процедура.имя.лок.clone() });
-            пп.код.push(Инструкция{ вид: ВидИнструкции::АргументНаСтек, лок: процедура.имя.лок.clone() }); // сохраняем адрес возврата на второй стек
-            for параметр in скомп_процедура.параметры.iter() {
-                пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(параметр.смещение), лок: параметр.имя.лок.clone() });
-                записать_тип(&параметр.имя.лок, &mut пп.код, &имена.структуры, &параметр.тип)?;
-            }
-            let mut отложенные_возвраты = Vec::new();
-            let mut локальные_имена = ЛокальныеИмена::default();
-            локальные_имена.стек_областей.push(Default::default());
-            for утверждение in &блок {
-                скомпилировать_утверждение(пп, имена, &скомп_процедура, &mut локальные_имена, утверждение, &mut отложенные_возвраты)?;
-            }
-            let размер_области = локальные_имена
-                .стек_областей
-                .pop()
-                .expect("Тело процедуры не трогает область которую мы протолкнули")
-                .размер_переменных_на_стеке;
-            if размер_области > 0 {
-                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_области as u64), лок: процедура.имя.лок.clone() });
-            }
-
-            let конец_процедуры = пп.код.len();
-            for адрес in отложенные_возвраты {
-                let инструкция = пп.код.get_mut(адрес).expect("отложенные возвраты из процедуры: некорретный адрес");
-                assert!(инструкция.вид == ВидИнструкции::Прыжок(0), "отложенные возвраты из процедуры: некорректная инструкция: {:?}", инструкция.вид);
-                инструкция.вид = ВидИнструкции::Прыжок(конец_процедуры);
-            }
-            if let Some(результат) = &скомп_процедура.результат {
-                пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(результат.смещение_от_кадра), лок: процедура.имя.лок.clone() });
-                if результат.тип.примитивный() {
-                    прочитать_примитивный_тип(&процедура.имя.лок, &mut пп.код, &имена.структуры, &результат.тип)?;
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::АргументСоСтека, лок: процедура.имя.лок.clone() });
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::ВосстановитьКадр, лок: процедура.имя.лок.clone() });
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_параметров_на_стеке as u64), лок: процедура.имя.лок.clone() });
-                } else {
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::АргументСоСтека, лок: процедура.имя.лок.clone() });
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::ВосстановитьКадр, лок: процедура.имя.лок.clone() });
-                    пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_параметров_на_стеке as u64 - результат.тип.размер(&имена.структуры) as u64), лок: процедура.имя.лок.clone() });
-                }
-            } else {
-                пп.код.push(Инструкция{ вид: ВидИнструкции::АргументСоСтека, лок: процедура.имя.лок.clone() });
-                пп.код.push(Инструкция{ вид: ВидИнструкции::ВосстановитьКадр, лок: процедура.имя.лок.clone() });
-                пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_параметров_на_стеке as u64), лок: процедура.имя.лок.clone() });
-            }
-            пп.код.push(Инструкция{ вид: ВидИнструкции::Возврат, лок: процедура.имя.лок.clone() });
-            Ok(())
+    let конец_процедуры = пп.код.len();
+    for адрес in отложенные_возвраты {
+        let инструкция = пп.код.get_mut(адрес).expect("отложенные возвраты из процедуры: некорретный адрес");
+        assert!(инструкция.вид == ВидИнструкции::Прыжок(0), "отложенные возвраты из процедуры: некорректная инструкция: {:?}", инструкция.вид);
+        инструкция.вид = ВидИнструкции::Прыжок(конец_процедуры);
+    }
+    if let Some(результат) = &скомп_процедура.результат {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::Кадр(результат.смещение_от_кадра), лок: процедура.имя.лок.clone() });
+        if результат.тип.примитивный() {
+            прочитать_примитивный_тип(&процедура.имя.лок, &mut пп.код, &имена.структуры, &результат.тип)?;
+            пп.код.push(Инструкция{ вид: ВидИнструкции::АргументСоСтека, лок: процедура.имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ВосстановитьКадр, лок: процедура.имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_параметров_на_стеке as u64), лок: процедура.имя.лок.clone() });
+        } else {
+            пп.код.push(Инструкция{ вид: ВидИнструкции::АргументСоСтека, лок: процедура.имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ВосстановитьКадр, лок: процедура.имя.лок.clone() });
+            пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_параметров_на_стеке as u64 - результат.тип.размер(&имена.структуры) as u64), лок: процедура.имя.лок.clone() });
         }
-        ТелоПроцедуры::Внешнее{символ} => {
-            let индекс = пп.внешние_символы.len();
-            let индекс = *пп.внешние_символы.entry(символ.текст).or_insert(индекс);
-            let скомп_процедура = СкомпПроцедура{
-                имя: процедура.имя,
-                параметры,
-                точка_входа: ТочкаВхода::Внешняя{индекс},
-                результат,
-            };
-            if let Some(_) = имена.процедуры.insert(скомп_процедура.имя.текст.clone(), скомп_процедура) {
-                unreachable!("Проверка переопределения процедур должна происходить на этапе разбора")
-            }
-            Ok(())
+    } else {
+        пп.код.push(Инструкция{ вид: ВидИнструкции::АргументСоСтека, лок: процедура.имя.лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ВосстановитьКадр, лок: процедура.имя.лок.clone() });
+        пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(размер_параметров_на_стеке as u64), лок: процедура.имя.лок.clone() });
+    }
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Возврат, лок: процедура.имя.лок.clone() });
+
+    if let Some(результат) = &скомп_процедура.результат {
+        if блок_может_завершиться(блок) {
+            диагностика!(&конец.лок, "ОШИБКА", "Процедура «{имя}» может завершиться, не вернув значение типа «{тип}»",
+                         имя = процедура.имя.текст,
+                         тип = результат.тип.текст());
+            диагностика!(&процедура.имя.лок, "ИНФО", "Процедура определена тут.");
+            return Err(());
         }
     }
+    Ok(())
 }
 
-fn скомпилировать_константное_выражение(пп: &ПП, имена: &Имена, локальные_имена: Option<&ЛокальныеИмена>, выражение: &Выражение) -> Результат<(Vec<u8>, Тип)> {
-    let mut временный_размер = 0;
-    let mut пп = (*пп).clone();
-    пп.код.clear();
-    пп.внешние_символы.clear();
-    пп.библиотеки.clear();
-    let текущая_процедура = СкомпПроцедура {
+/// Может ли исполнение дойти до конца блока, т.е. не упереться во
+/// «вернуть», «прервать», «продолжить» или бесконечный цикл.
+fn блок_может_завершиться(блок: &[Утверждение]) -> bool {
+    блок.iter().all(утверждение_может_завершиться)
+}
+
+fn утверждение_может_завершиться(утверждение: &Утверждение) -> bool {
+    match утверждение {
+        Утверждение::Вернуть{..} | Утверждение::Прервать{..} | Утверждение::Продолжить{..} => false,
+        Утверждение::Если{тело, иначе, ..} => блок_может_завершиться(тело) || блок_может_завершиться(иначе),
+        Утверждение::Вилка{ветки, любое, ..} => match любое {
+            Some((_, любое_тело)) => блок_может_завершиться(любое_тело) || ветки.iter().any(|ветка| блок_может_завершиться(&ветка.тело)),
+            // Даже полная вилка по перечислению может не найти подходящую
+            // ветку, если значение было получено через «как».
+            None => true,
+        },
+        // Только «пока истина» без выхода из него считаем бесконечным.
+        // Остальные циклы могут не выполнить тело ни разу.
+        Утверждение::Пока{условие: Выражение::Лог(_, true), метка, тело, ..} => выходит_из_цикла(тело, метка, false),
+        Утверждение::Пока{..} | Утверждение::Для{..} | Утверждение::ДляКаждого{..} => true,
+        Утверждение::Присваивание{..} |
+        Утверждение::Выражение{..} |
+        Утверждение::ДекларацияПеременной{..} |
+        Утверждение::ДекларацияКонстанты{..} => true,
+    }
+}
+
+/// Есть ли в теле цикла «прервать», которое выходит именно из этого цикла.
+fn выходит_из_цикла(тело: &[Утверждение], метка: &Option<Лексема>, во_вложенном_цикле: bool) -> bool {
+    тело.iter().any(|утверждение| match утверждение {
+        Утверждение::Прервать{метка: None, ..} => !во_вложенном_цикле,
+        Утверждение::Прервать{метка: Some(цель), ..} => метка.as_ref().map_or(false, |метка| метка.текст == цель.текст),
+        Утверждение::Если{тело, иначе, ..} => выходит_из_цикла(тело, метка, во_вложенном_цикле) || выходит_из_цикла(иначе, метка, во_вложенном_цикле),
+        Утверждение::Вилка{ветки, любое, ..} => {
+            ветки.iter().any(|ветка| выходит_из_цикла(&ветка.тело, метка, во_вложенном_цикле)) ||
+                любое.as_ref().map_or(false, |(_, любое_тело)| выходит_из_цикла(любое_тело, метка, во_вложенном_цикле))
+        }
+        Утверждение::Пока{тело, ..} | Утверждение::Для{тело, ..} | Утверждение::ДляКаждого{тело, ..} => выходит_из_цикла(тело, метка, true),
+        _ => false,
+    })
+}
+
+/// У «пусто» нет своего типа, его можно присвоить любому указателю.
+fn проверить_выведенный_тип(значение: &Выражение, тип: &Тип) -> Результат<()> {
+    if let Тип::Пусто = тип {
+        диагностика!(значение.лок(), "ОШИБКА", "Невозможно вывести тип переменной из «пусто». Укажите тип явно.");
+        return Err(());
+    }
+    проверить_литерал_по_умолчанию(значение, тип)
+}
+
+/// Процедура, от имени которой компилируется код вне настоящих процедур:
+/// константные выражения и начальные значения глобальных переменных.
+fn служебная_процедура(имя: &str, лок: &Лок) -> СкомпПроцедура {
+    СкомпПроцедура {
         имя: Лексема {
             вид: ВидЛексемы::Идент,
-            текст: "<Констатное Выражение>".to_string(),
-            лок: выражение.лок().clone(),
+            текст: имя.to_string(),
+            лок: лок.clone(),
         },
         параметры: vec![],
         результат: None,
-        точка_входа: ТочкаВхода::Внутреняя{адрес: 0},
-    };
-    let mut имена = имена.clone();
-    имена.процедуры.clear();
-    имена.переменные.clear();
-    let mut локальные_имена = локальные_имена.cloned().unwrap_or_else(|| ЛокальныеИмена::default());
-    for область in локальные_имена.стек_областей.iter_mut() {
-        область.переменные.clear();
-        область.размер_переменных_на_стеке = 0;
+        точка_входа: ТочкаВхода::Внутреняя{адрес: АДРЕС_НЕИЗВЕСТЕН},
+        модуль: None,
+        скрытая: false,
     }
-    let тип = скомпилировать_выражение(&mut пп, &имена, &текущая_процедура, &локальные_имена, выражение, &mut временный_размер)?;
+}
+
+/// Значение константного выражения, вычисленное во временной машине.
+struct ЗначениеКонстанты {
+    тип: Тип,
+    байты: Vec<u8>,
+    /// Инициализированные данные, которые появились во время вычисления,
+    /// например строковые литералы. Во временной машине они шли сразу за
+    /// данными исходного ПП.
+    новые_данные: Vec<u8>,
+    новые_строки: Vec<(String, usize)>,
+    /// Смещения внутри «байты», по которым лежат указатели на
+    /// инициализированные данные. Указатели уже переведены в смещения от
+    /// начала данных.
+    указатели: Vec<usize>,
+}
+
+/// Перенести значение константы в ПП. Примитивные значения хранятся в самой
+/// константе, остальные в инициализированных данных.
+fn разместить_константу(пп: &mut ПП, имя: Лексема, модуль: Option<String>, значение: ЗначениеКонстанты) -> СкомпКонстанта {
+    let тип = значение.тип.clone();
     if !тип.примитивный() {
-        // СДЕЛАТЬ: Компиляция константных выражений сложных типов
-        // Главная проблема здесь в том, что любое выделение
-        // инициализированных данных при компиляции константного
-        // выражения уходит во временную машину. Это особенно
-        // проблемно для строковых литералов:
-        //
-        //     конст СООБЩЕНИЕ := «Привет, «Мир»!»;
-        //
-        //     про главная() нч
-        //         печать(СООБЩЕНИЕ, «\н»);12
-        //     кц
-        //
-        // Данная программа будет сегфолтиться, т.к. сообщения
-        // «Привет, «Мир»!» в памяти главное машины не
-        // существует. Адрес константы СООБЩЕНИЯ ссылается в никуда.
-
-        диагностика!(выражение.лок(), "ОШИБКА", "На данный момент разрешена компиляция константных выражений только примитивных типов");
-        return Err(());
+        let адрес = разместить_значение(пп, значение) as u64;
+        return СкомпКонстанта { имя, тип, значение_или_адрес: адрес, в_иниц_данных: true, модуль };
     }
-    пп.код.push(Инструкция{ вид: ВидИнструкции::Возврат, лок: выражение.лок().clone() });
-    let mut машина = Машина::новая(&пп, 1024);
-    let _ = машина.интерпретировать(&имена, 0, false)?;
-    let значение_или_адрес = машина.стек.pop().unwrap();
-    assert!(машина.стек.len() == 1); // Содержит только адрес возврата
-    if тип.примитивный() {
-        return Ok((значение_или_адрес.to_le_bytes().to_vec(), тип));
-    } else {
-        return Ok((машина.срез_памяти(значение_или_адрес, тип.размер(&имена.структуры))?.to_vec(), тип));
+    // Указатель внутрь инициализированных данных уже переведён в смещение,
+    // но данные, на которые он указывает, могли появиться только во время
+    // вычисления.
+    let в_иниц_данных = !значение.указатели.is_empty();
+    if в_иниц_данных {
+        пп.иниц_данные.extend(значение.новые_данные);
+        for (строка, указатель) in значение.новые_строки {
+            пп.строки.insert(строка, указатель);
+        }
     }
+    let значение_или_адрес = u64::from_le_bytes(значение.байты.try_into().unwrap());
+    СкомпКонстанта { имя, тип, значение_или_адрес, в_иниц_данных, модуль }
 }
 
-fn скомпилировать_тип(пп: &ПП, тип: &Выражение, имена: &Имена, локальные_имена: Option<&ЛокальныеИмена>) -> Результат<Тип> {
+/// Записать значение в инициализированные данные и вернуть его адрес.
+fn разместить_значение(пп: &mut ПП, значение: ЗначениеКонстанты) -> usize {
+    пп.иниц_данные.extend(значение.новые_данные);
+    for (строка, указатель) in значение.новые_строки {
+        пп.строки.insert(строка, указатель);
+    }
+    let адрес = пп.иниц_данные.len();
+    пп.указатели_в_иниц_данных.extend(значение.указатели.iter().map(|смещение| адрес + смещение));
+    пп.иниц_данные.extend(значение.байты);
+    адрес
+}
+
+/// Смещения всех указателей внутри значения данного типа.
+fn собрать_указатели(тип: &Тип, смещение: usize, структуры: &HashMap<String, Структура>, указатели: &mut Vec<usize>) {
     match тип {
-        Выражение::Лог(лексема, _) => {
-            диагностика!(&лексема.лок, "ОШИБКА", "Логические выражения не являться типами");
-            Err(())
+        Тип::Указатель{..} => указатели.push(смещение),
+        Тип::Срез{..} => указатели.push(смещение + СРЕЗ_АДРЕС_СМЕЩЕНИЕ),
+        Тип::Массив{тип_элемента, размер} => {
+            let размер_элемента = тип_элемента.размер(структуры);
+            for индекс in 0..*размер {
+                собрать_указатели(тип_элемента, смещение + индекс*размер_элемента, структуры, указатели);
+            }
         }
-        Выражение::ЦелЧисло(лексема, _) => {
-            диагностика!(&лексема.лок, "ОШИБКА", "Целое число не является являться типом");
-            Err(())
+        Тип::Структура(имя) => {
+            let структура = структуры.get(имя).expect("Существование структуры должно быть уже проверено на этапе компиляции типа");
+            for поле in структура.поля.values() {
+                собрать_указатели(&поле.тип, смещение + поле.смещение, структуры, указатели);
+            }
+        }
+        Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 | Тип::Вещ32 | Тип::Вещ64 | Тип::Символ | Тип::Лог | Тип::Перечисление(_) | Тип::Пусто => {}
+    }
+}
+
+fn скомпилировать_константное_выражение(пп: &ПП, имена: &Имена, локальные_имена: Option<&ЛокальныеИмена>, выражение: &Выражение) -> Результат<ЗначениеКонстанты> {
+    let mut временный_размер = 0;
+    let исходный_размер_иниц_данных = пп.иниц_данные.len();
+    // Код уже скомпилированных процедур остаётся на месте, чтобы константы
+    // могли их вызывать. Само выражение дописывается в конец.
+    let mut пп = (*пп).clone();
+    пп.точка_инициализации = None;
+    let точка_входа = пп.код.len();
+    let текущая_процедура = служебная_процедура("<Констатное Выражение>", выражение.лок());
+    let mut имена = имена.clone();
+    let глобальные_переменные: Vec<СкомпПеременная> = имена.переменные.drain().map(|(_, переменная)| переменная).collect();
+    let mut локальные_имена = локальные_имена.cloned().unwrap_or_else(|| ЛокальныеИмена::default());
+    for область in локальные_имена.стек_областей.iter_mut() {
+        область.переменные.clear();
+        область.размер_переменных_на_стеке = 0;
+    }
+    let тип = скомпилировать_выражение(&mut пп, &имена, &текущая_процедура, &локальные_имена, выражение, &mut временный_размер)?;
+    пп.код.push(Инструкция{ вид: ВидИнструкции::Возврат, лок: выражение.лок().clone() });
+    for &(индекс, ref ключ) in &пп.заплатки_вызовов {
+        let Some(СкомпПроцедура{имя, точка_входа: ТочкаВхода::Внутреняя{адрес}, ..}) = имена.процедуры.get(ключ) else {
+            unreachable!("Заплатка вызова должна ссылаться на внутреннюю процедуру");
+        };
+        if *адрес == АДРЕС_НЕИЗВЕСТЕН {
+            // Вызовы из тел других процедур нас не интересуют, лишь бы их
+            // не было в самом выражении.
+            if индекс >= точка_входа {
+                диагностика!(&пп.код[индекс].лок, "ОШИБКА", "Процедура «{имя}» ещё не скомпилирована, поэтому её нельзя вызвать здесь", имя = имя.текст);
+                диагностика!(&имя.лок, "ИНФО", "Процедура определена здесь");
+                return Err(());
+            }
+            continue;
+        }
+        пп.код[индекс].вид = ВидИнструкции::ВнутреннийВызов(*адрес);
+    }
+    // Глобальные переменные во время вычисления живут в неинициализированных
+    // данных временной машины.
+    for &индекс in &пп.заплатки_неиниц_указателей {
+        if let ВидИнструкции::ГлобальныеДанные(указатель) = &mut пп.код[индекс].вид {
+            *указатель += пп.иниц_данные.len() as i32;
+        }
+    }
+    let mut машина = Машина::новая(&пп, 1024);
+    машина.лимит_инструкций = Some(ЛИМИТ_ИНСТРУКЦИЙ_КОНСТАНТЫ);
+    машина.только_чистые_вычисления = true;
+    // Глобальные переменные получают значения только во время исполнения
+    // программы, поэтому процедурам, вызванным из константы, их трогать
+    // нельзя.
+    машина.глобальные_переменные = глобальные_переменные.iter().map(|переменная| {
+        let начало = if переменная.в_иниц_данных {
+            переменная.смещение as usize
+        } else {
+            пп.иниц_данные.len() + переменная.смещение as usize
+        };
+        начало..начало + переменная.тип.размер(&имена.структуры)
+    }).collect();
+    if машина.интерпретировать(&имена, точка_входа, false).is_err() {
+        диагностика!(выражение.лок(), "ИНФО", "Ошибка произошла при вычислении константного выражения");
+        return Err(());
+    }
+    let значение_или_адрес = машина.стек.pop().unwrap();
+    assert!(машина.стек.len() == 1); // Содержит только адрес возврата
+    if let Тип::Указатель{..} = тип {
+        if значение_или_адрес != 0 {
+            // В отличие от срезов, указатель за конец данных нельзя
+            // разыменовать, а куча может начинаться сразу за ними.
+            let Some(смещение_в_данных) = машина
+                .смещение_в_иниц_данных(значение_или_адрес, пп.иниц_данные.len())
+                .filter(|смещение| *смещение < пп.иниц_данные.len()) else {
+                диагностика!(выражение.лок(), "ОШИБКА", "Значение константы ссылается на память, которая существует только во время её вычисления");
+                return Err(());
+            };
+            return Ok(ЗначениеКонстанты {
+                тип,
+                байты: (смещение_в_данных as u64).to_le_bytes().to_vec(),
+                новые_данные: пп.иниц_данные[исходный_размер_иниц_данных..].to_vec(),
+                новые_строки: пп.строки.into_iter().filter(|(_, указатель)| *указатель >= исходный_размер_иниц_данных).collect(),
+                указатели: vec![0],
+            });
+        }
+    }
+    if тип.примитивный() {
+        return Ok(ЗначениеКонстанты {
+            тип,
+            байты: значение_или_адрес.to_le_bytes().to_vec(),
+            новые_данные: vec![],
+            новые_строки: vec![],
+            указатели: vec![],
+        });
+    }
+
+    // Строки и прочие данные, выделенные во время вычисления, живут во
+    // временной машине. Переносим их в настоящее ПП сразу за уже
+    // существующими данными, а указатели на них переводим в смещения от
+    // начала данных.
+    let начало_новых_данных = исходный_размер_иниц_данных;
+    let mut байты = машина.срез_памяти(значение_или_адрес, тип.размер(&имена.структуры))?.to_vec();
+    let mut указатели = Vec::new();
+    собрать_указатели(&тип, 0, &имена.структуры, &mut указатели);
+    let mut указатели_на_данные = Vec::new();
+    for смещение in указатели {
+        let указатель = u64::from_le_bytes(байты[смещение..смещение + РАЗМЕР_СЛОВА].try_into().unwrap()) as usize;
+        if указатель == 0 {
+            continue;
+        }
+        let Some(смещение_в_данных) = машина.смещение_в_иниц_данных(указатель, пп.иниц_данные.len()) else {
+            диагностика!(выражение.лок(), "ОШИБКА", "Значение константы ссылается на память, которая существует только во время её вычисления");
+            return Err(());
+        };
+        байты[смещение..смещение + РАЗМЕР_СЛОВА].copy_from_slice(&(смещение_в_данных as u64).to_le_bytes());
+        указатели_на_данные.push(смещение);
+    }
+    Ok(ЗначениеКонстанты {
+        тип,
+        байты,
+        новые_данные: пп.иниц_данные[начало_новых_данных..].to_vec(),
+        новые_строки: пп.строки.into_iter().filter(|(_, указатель)| *указатель >= начало_новых_данных).collect(),
+        указатели: указатели_на_данные,
+    })
+}
+
+fn скомпилировать_тип(пп: &ПП, тип: &Выражение, имена: &Имена, локальные_имена: Option<&ЛокальныеИмена>) -> Результат<Тип> {
+    match тип {
+        Выражение::Лог(лексема, _) => {
+            диагностика!(&лексема.лок, "ОШИБКА", "Логические выражения не являться типами");
+            Err(())
+        }
+        Выражение::ЦелЧисло(лексема, _) => {
+            диагностика!(&лексема.лок, "ОШИБКА", "Целое число не является являться типом");
+            Err(())
         }
         Выражение::НатЧисло(лексема, _) => {
             диагностика!(&лексема.лок, "ОШИБКА", "Натуральное число не является являться типом");
             Err(())
         }
-        Выражение::ВещЧисло(лексема, _) => {
+        Выражение::ВещЧисло(лексема, _) | Выражение::Вещ64Число(лексема, _) => {
             диагностика!(&лексема.лок, "ОШИБКА", "Вещественное число не является являться типом");
             Err(())
         }
+        Выражение::Символ(лексема, _) => {
+            диагностика!(&лексема.лок, "ОШИБКА", "Символ не является являться типом");
+            Err(())
+        }
         Выражение::Строка(лексема) => {
             диагностика!(&лексема.лок, "ОШИБКА", "Строка не является являться типом");
             Err(())
         }
+        Выражение::ЦепочкаСтроки{строка, ..} => {
+            диагностика!(&строка.лок, "ОШИБКА", "Строка не является являться типом");
+            Err(())
+        }
+        Выражение::Диапазон{ключ, ..} => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Диапазон не является типом");
+            Err(())
+        }
         Выражение::Идент(лексема) => match лексема.текст.as_str() {
             "цел" | "цел64" => Ok(Тип::Цел64),
             "нат" | "нат64" => Ok(Тип::Нат64),
             "нат8" => Ok(Тип::Нат8),
+            "нат16" => Ok(Тип::Нат16),
+            "нат32" => Ok(Тип::Нат32),
+            "цел8" => Ok(Тип::Цел8),
+            "цел16" => Ok(Тип::Цел16),
+            "цел32" => Ok(Тип::Цел32),
             "вещ" | "вещ32" => Ok(Тип::Вещ32),
+            "вещ64" => Ok(Тип::Вещ64),
+            "символ" => Ok(Тип::Символ),
             "строка" => Ok(Тип::Срез { тип_элемента: Box::new(Тип::Нат8) }),
             "лог" => Ok(Тип::Лог),
             _ => {
                 if имена.структуры.contains_key(&лексема.текст) {
                     Ok(Тип::Структура(лексема.текст.clone()))
+                } else if имена.перечисления.contains_key(&лексема.текст) {
+                    Ok(Тип::Перечисление(лексема.текст.clone()))
                 } else {
                     диагностика!(&лексема.лок, "ОШИБКА", "Неизвестный тип «{тип}»", тип = лексема.текст);
                     Err(())
@@ -2301,12 +4052,26 @@ fn скомпилировать_тип(пп: &ПП, тип: &Выражение,
                                              аргументов = ЧИСУЩ_АРГУМЕНТ.текст(количество_аргументов));
                                 return Err(())
                             };
-                            let (байты_размера, тип_размера) = скомпилировать_константное_выражение(&пп, имена, локальные_имена, &выражение_размера)?;
-                            проверить_типы(выражение_размера.лок(), &Тип::Цел64, &тип_размера)?;
-                            let размер = usize::from_le_bytes(байты_размера.try_into().unwrap());
+                            let значение_размера = скомпилировать_константное_выражение(&пп, имена, локальные_имена, &выражение_размера)?;
+                            проверить_типы(выражение_размера.лок(), &Тип::Цел64, &значение_размера.тип)?;
+                            let размер = usize::from_le_bytes(значение_размера.байты.try_into().unwrap());
                             let тип_элемента = Box::new(скомпилировать_тип(пп, &тип_элемента, имена, локальные_имена)?);
                             Ok(Тип::Массив{размер, тип_элемента})
                         }
+                        "указатель" => {
+                            let [тип_элемента] = аргументы.выражения.as_slice() else {
+                                let количество_параметров = 1;
+                                let количество_аргументов = аргументы.выражения.len();
+                                диагностика!(&имя.лок, "ОШИБКА",
+                                             "Неверное количество аргументов параметризированного типа. Тип «{имя}» принимает {количество_параметров} {параметров}, но предоставлено лишь {количество_аргументов} {аргументов}.",
+                                             имя = имя.текст,
+                                             параметров = ЧИСУЩ_ПАРАМЕТР.текст(количество_параметров),
+                                             аргументов = ЧИСУЩ_АРГУМЕНТ.текст(количество_аргументов));
+                                return Err(())
+                            };
+                            let тип_элемента = Box::new(скомпилировать_тип(пп, &тип_элемента, имена, локальные_имена)?);
+                            Ok(Тип::Указатель{тип_элемента})
+                        }
                         "срез" => {
                             let [тип_элемента] = аргументы.выражения.as_slice() else {
                                 let количество_параметров = 1;
@@ -2346,136 +4111,938 @@ fn скомпилировать_тип(пп: &ПП, тип: &Выражение,
             // Хотя, это довольно таки интересна идея. Тип «не цел». Любой тип, кроме целого...
             Err(())
         }
+        Выражение::Адрес{ключ, ..} | Выражение::Разыменование{ключ, ..} => {
+            диагностика!(&ключ.лок, "ОШИБКА", "Операции над указателями не поддерживаются в типах. Используйте «указатель(тип)».");
+            Err(())
+        }
+        Выражение::Пусто(лексема) => {
+            диагностика!(&лексема.лок, "ОШИБКА", "«пусто» не является типом");
+            Err(())
+        }
+    }
+}
+
+/// Определение верхнего уровня, которое уже разобрано, но ещё не
+/// скомпилировано.
+enum Определение {
+    Переменная(Переменная),
+    Процедура {
+        процедура: Процедура,
+        модуль: Option<String>,
+        скрытая: bool,
+        /// Тело с синтаксическими ошибками не компилируем, чтобы не плодить
+        /// ложных ошибок. Но саму процедуру регистрируем, чтобы её вызовы в
+        /// других процедурах проверялись как обычно.
+        ошибки_разбора: bool,
+    },
+    Константа {
+        константа: Константа,
+        модуль: Option<String>,
+    },
+    Структура {
+        имя: Лексема,
+        поля: Vec<(Лексема, Выражение)>,
+    },
+    Перечисление(Перечисление),
+}
+
+/// Все идентификаторы, на которые ссылается выражение, включая имена
+/// вызываемых процедур. Имена полей справа от «.» не считаются, но вызовы
+/// справа от «.» считаются, т.к. так вызываются процедуры модулей.
+fn собрать_идентификаторы<'ы>(выражение: &'ы Выражение, идентификаторы: &mut Vec<&'ы Лексема>) {
+    match выражение {
+        Выражение::Идент(лексема) => идентификаторы.push(лексема),
+        Выражение::ЦепочкаВызовов{имя, цепочка_аргументов} => {
+            идентификаторы.push(имя);
+            for аргументы in цепочка_аргументов {
+                for аргумент in &аргументы.выражения {
+                    собрать_идентификаторы(аргумент, идентификаторы);
+                }
+            }
+        }
+        Выражение::ЦепочкаСтроки{цепочка_аргументов, ..} => {
+            for аргументы in цепочка_аргументов {
+                for аргумент in &аргументы.выражения {
+                    собрать_идентификаторы(аргумент, идентификаторы);
+                }
+            }
+        }
+        Выражение::Диапазон{от, до, ..} => {
+            for граница in от.iter().chain(до.iter()) {
+                собрать_идентификаторы(граница, идентификаторы);
+            }
+        }
+        Выражение::Биноп{вид: ВидБинопа::Поле, левое, правое, ..} => {
+            собрать_идентификаторы(левое, идентификаторы);
+            if let Выражение::ЦепочкаВызовов{..} = правое.as_ref() {
+                собрать_идентификаторы(правое, идентификаторы);
+            }
+        }
+        Выражение::Биноп{левое, правое, ..} => {
+            собрать_идентификаторы(левое, идентификаторы);
+            собрать_идентификаторы(правое, идентификаторы);
+        }
+        Выражение::УнарныйМинус{выражение, ..} |
+        Выражение::Отрицание{выражение, ..} |
+        Выражение::Адрес{выражение, ..} |
+        Выражение::Разыменование{выражение, ..} => собрать_идентификаторы(выражение, идентификаторы),
+        Выражение::ЦелЧисло(..) |
+        Выражение::НатЧисло(..) |
+        Выражение::ВещЧисло(..) |
+        Выражение::Вещ64Число(..) |
+        Выражение::Символ(..) |
+        Выражение::Лог(..) |
+        Выражение::Строка(..) |
+        Выражение::Пусто(..) => {}
+    }
+}
+
+/// Идентификаторы, на которые ссылается блок кода. В «вычисляемые»
+/// попадают те, что встречаются в выражениях, вычисляемых во время
+/// компиляции: в локальных константах и типах переменных.
+fn собрать_идентификаторы_блока<'ы>(блок: &'ы [Утверждение], идентификаторы: &mut Vec<&'ы Лексема>, вычисляемые: &mut Vec<&'ы Лексема>) {
+    for утверждение in блок {
+        match утверждение {
+            Утверждение::Присваивание{левое, правое, ..} => {
+                собрать_идентификаторы(левое, идентификаторы);
+                собрать_идентификаторы(правое, идентификаторы);
+            }
+            Утверждение::Выражение{выражение} => собрать_идентификаторы(выражение, идентификаторы),
+            Утверждение::Пока{условие, тело, ..} => {
+                собрать_идентификаторы(условие, идентификаторы);
+                собрать_идентификаторы_блока(тело, идентификаторы, вычисляемые);
+            }
+            Утверждение::Для{нижняя_граница, верхняя_граница, тело, ..} => {
+                собрать_идентификаторы(нижняя_граница, идентификаторы);
+                собрать_идентификаторы(верхняя_граница, идентификаторы);
+                собрать_идентификаторы_блока(тело, идентификаторы, вычисляемые);
+            }
+            Утверждение::ДляКаждого{коллекция, тело, ..} => {
+                собрать_идентификаторы(коллекция, идентификаторы);
+                собрать_идентификаторы_блока(тело, идентификаторы, вычисляемые);
+            }
+            Утверждение::Если{условие, тело, иначе, ..} => {
+                собрать_идентификаторы(условие, идентификаторы);
+                собрать_идентификаторы_блока(тело, идентификаторы, вычисляемые);
+                собрать_идентификаторы_блока(иначе, идентификаторы, вычисляемые);
+            }
+            Утверждение::Вернуть{выражение, ..} => if let Some(выражение) = выражение {
+                собрать_идентификаторы(выражение, идентификаторы);
+            }
+            Утверждение::ДекларацияПеременной{тип, значение, ..} => {
+                if let Some(тип) = тип {
+                    собрать_идентификаторы(тип, вычисляемые);
+                }
+                if let Some(значение) = значение {
+                    собрать_идентификаторы(значение, идентификаторы);
+                }
+            }
+            Утверждение::ДекларацияКонстанты{значение, ..} => собрать_идентификаторы(значение, вычисляемые),
+            Утверждение::Вилка{выражение, ветки, любое, ..} => {
+                собрать_идентификаторы(выражение, идентификаторы);
+                for ветка in ветки {
+                    for образец in &ветка.образцы {
+                        match образец {
+                            ОбразецВилки::Значение(значение) => собрать_идентификаторы(значение, идентификаторы),
+                            ОбразецВилки::Диапазон{от, до, ..} => {
+                                собрать_идентификаторы(от, идентификаторы);
+                                собрать_идентификаторы(до, идентификаторы);
+                            }
+                        }
+                    }
+                    собрать_идентификаторы_блока(&ветка.тело, идентификаторы, вычисляемые);
+                }
+                if let Some((_, тело)) = любое {
+                    собрать_идентификаторы_блока(тело, идентификаторы, вычисляемые);
+                }
+            }
+            Утверждение::Прервать{..} | Утверждение::Продолжить{..} => {}
+        }
+    }
+}
+
+/// Ключ процедуры, которую вызывают по имени из модуля. Скрытые процедуры
+/// самого модуля перекрывают общие.
+fn ключ_вызова(модуль: &Option<String>, имя: &str, существует: impl Fn(&str) -> bool) -> Option<String> {
+    if let Some(модуль) = модуль {
+        let ключ = format!("{модуль}.{имя}");
+        if существует(&ключ) {
+            return Some(ключ);
+        }
+    }
+    if существует(имя) {
+        return Some(имя.to_string());
+    }
+    None
+}
+
+/// Тела процедур и константы, которые вызывают процедуры. Их нельзя
+/// скомпилировать в общем порядке: константе нужны скомпилированные тела
+/// всех процедур, которые она может вызвать, а процедуре нужны значения
+/// всех констант, которые она использует.
+#[derive(Default)]
+struct ОтложенныеОпределения {
+    тела: HashMap<String, (Процедура, Option<String>, usize)>,
+    константы: HashMap<String, (Константа, Option<String>)>,
+    /// Процедуры, которые может вызвать каждое скомпилированное тело.
+    вызовы: HashMap<String, Vec<String>>,
+    неудачные: HashSet<String>,
+    /// Ключи процедур и имена констант, которые сейчас компилируются.
+    в_процессе: Vec<(String, Лексема)>,
+}
+
+/// Пока раскладываются структуры и регистрируются сигнатуры процедур,
+/// ни одно тело ещё не скомпилировано, и константы, которые вызывают
+/// процедуры, ещё не вычислены.
+fn пояснить_отложенные_константы<'ы>(отложенные: &ОтложенныеОпределения, типы: impl IntoIterator<Item = &'ы Выражение>) {
+    let mut зависимости = Vec::new();
+    for тип in типы {
+        собрать_идентификаторы(тип, &mut зависимости);
+    }
+    for зависимость in зависимости {
+        if отложенные.константы.contains_key(&зависимость.текст) {
+            диагностика!(&зависимость.лок, "ИНФО", "Константа «{имя}» вызывает процедуры и поэтому вычисляется позже. Её нельзя использовать в типах полей структур, глобальных переменных и параметров процедур.", имя = зависимость.текст);
+        }
+    }
+}
+
+/// Структуры, которые тип содержит по значению. Указатели и срезы не в
+/// счёт, их размер не зависит от размера элемента.
+fn собрать_структуры_по_значению<'ы>(тип: &'ы Тип, структуры: &mut Vec<&'ы str>) {
+    match тип {
+        Тип::Структура(имя) => структуры.push(имя),
+        Тип::Массив{тип_элемента, ..} => собрать_структуры_по_значению(тип_элемента, структуры),
+        _ => {}
     }
 }
 
 impl Программа {
+    /// Компиляция в два прохода. Сначала разбираем все определения, включая
+    /// определения из модулей, и только потом компилируем их. Поэтому
+    /// порядок определений в файле не имеет значения.
+    ///
+    /// После неудачного определения ищем начало следующего и продолжаем,
+    /// чтобы сообщить обо всех ошибках за один запуск, а не по одной.
     pub fn скомпилировать_лексемы(&mut self, лекс: &mut Лексер) -> Результат<()> {
+        let mut определения = Vec::new();
+        let mut результат = self.разобрать_лексемы(лекс, &mut определения, None);
+        if слишком_много_ошибок() {
+            return Err(());
+        }
+        if self.скомпилировать_определения(определения).is_err() {
+            результат = Err(());
+        }
+        результат
+    }
+
+    fn разобрать_лексемы(&mut self, лекс: &mut Лексер, определения: &mut Vec<Определение>, модуль: Option<&str>) -> Результат<()> {
+        let mut результат = Ok(());
         loop {
-            let ключ = лекс.вытащить_лексему_вида(&[
-                ВидЛексемы::КлючПер,
-                ВидЛексемы::КлючПро,
-                ВидЛексемы::КлючКонст,
-                ВидЛексемы::КлючВкл,
-                ВидЛексемы::КлючБибл,
-                ВидЛексемы::КлючСтрукт,
-                ВидЛексемы::Конец,
-            ])?;
-            match ключ.вид {
-                ВидЛексемы::КлючПер => {
-                    let синтаксис = Переменная::разобрать(лекс)?;
-                    let имя = синтаксис.имя;
-                    self.имена.верифицировать_переопределение_имени(&имя)?;
-                    let тип = скомпилировать_тип(&self.пп, &синтаксис.тип, &self.имена, None)?;
-                    let смещение = self.пп.размер_неиниц_данных as i32;
-                    self.пп.размер_неиниц_данных += тип.размер(&self.имена.структуры);
-                    if let Some(_) = self.имена.переменные.insert(имя.текст.clone(), СкомпПеременная {имя, тип, смещение}) {
-                        unreachable!("Проверка переопределения переменных должна происходить на этапе разбора")
-                    }
-                }
-                ВидЛексемы::КлючПро => {
-                    let процедура = Процедура::разобрать(лекс)?;
-                    self.имена.верифицировать_переопределение_имени(&процедура.имя)?;
-                    скомпилировать_процедуру(&mut self.пп, &mut self.имена, процедура)?;
-                }
-                ВидЛексемы::КлючКонст => {
-                    let константа = Константа::разобрать(лекс)?;
-                    self.имена.верифицировать_переопределение_имени(&константа.имя)?;
-                    let (байты_значения, тип) = скомпилировать_константное_выражение(&self.пп, &self.имена, None, &константа.выражение)?;
-                    if тип.примитивный() {
-                        let значение = u64::from_le_bytes(байты_значения.try_into().unwrap());
-                        if let Some(_) = self.имена.константы.insert(константа.имя.текст.clone(), СкомпКонстанта {
-                            имя: константа.имя.clone(),
-                            тип,
-                            значение_или_адрес: значение
-                        }) {
-                            unreachable!("Проверка переопределения констант должна происходить на этапе разбора")
-                        }
-                    } else {
-                        let адрес = self.пп.иниц_данные.len();
-                        self.пп.иниц_данные.extend(байты_значения);
-                        if let Some(_) = self.имена.константы.insert(константа.имя.текст.clone(), СкомпКонстанта {
-                            имя: константа.имя.clone(),
-                            тип,
-                            значение_или_адрес: адрес as u64,
-                        }) {
-                            unreachable!("Проверка переопределения констант должна происходить на этапе разбора")
-                        }
+            let столбец_определения = лекс.подсмотреть_лексему().map_or(1, |лексема| лексема.лок.столбец);
+            match self.разобрать_определение(лекс, определения, модуль) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(()) => {
+                    результат = Err(());
+                    if слишком_много_ошибок() {
+                        break;
                     }
+                    пропустить_определение(лекс, столбец_определения);
+                }
+            }
+        }
+        результат
+    }
+
+    /// Возвращает false, если ввод закончился.
+    fn разобрать_определение(&mut self, лекс: &mut Лексер, определения: &mut Vec<Определение>, модуль: Option<&str>) -> Результат<bool> {
+        let ключ = лекс.вытащить_лексему_вида(КЛЮЧИ_ОПРЕДЕЛЕНИЙ)?;
+        match ключ.вид {
+            ВидЛексемы::КлючПер => {
+                определения.push(Определение::Переменная(Переменная::разобрать(лекс)?));
+            }
+            ВидЛексемы::КлючПро | ВидЛексемы::КлючСкрыт => {
+                let скрытая = ключ.вид == ВидЛексемы::КлючСкрыт;
+                if скрытая {
+                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючПро])?;
                 }
-                ВидЛексемы::КлючВкл => {
-                    // СДЕЛАТЬ: расхардкодить пути поиска модулей
-                    let пути_поиска_модулей = &[".", "./модули"];
-                    // СДЕЛАТЬ: отслеживать повторное включение модулей и игнорировать его.
+                let ошибок_до_разбора = количество_ошибок();
+                let процедура = Процедура::разобрать(лекс)?;
+                let ошибки_разбора = количество_ошибок() > ошибок_до_разбора;
+                определения.push(Определение::Процедура {
+                    процедура,
+                    модуль: модуль.map(String::from),
+                    скрытая,
+                    ошибки_разбора,
+                });
+                if ошибки_разбора {
+                    return Err(());
+                }
+                // Главный файл никто не включает, так что скрывать в нём не
+                // от кого. Процедуру всё равно регистрируем, чтобы её
+                // вызовы не порождали лишних ошибок.
+                if скрытая && модуль.is_none() {
+                    диагностика!(&ключ.лок, "ОШИБКА", "Скрывать процедуры можно только в модулях, подключаемых через «вкл»");
+                    return Err(());
+                }
+            }
+            ВидЛексемы::КлючКонст => {
+                определения.push(Определение::Константа {
+                    константа: Константа::разобрать(лекс)?,
+                    модуль: модуль.map(String::from),
+                });
+            }
+            ВидЛексемы::КлючВкл => {
+                let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
+                let Some(путь_к_файлу) = self.найти_модуль(&имя.текст) else {
+                    диагностика!(&имя.лок, "ОШИБКА", "Не удалось найти модуль «{имя}».", имя = имя.текст);
+                    диагностика!(&имя.лок, "ИНФО", "Модули ищутся в каталогах: {каталоги}",
+                                 каталоги = self.каталоги_поиска_модулей()
+                                     .map(|каталог| format!("«{}»", каталог.display()))
+                                     .collect::<Vec<_>>()
+                                     .join(", "));
+                    return Err(());
+                };
+                // Один и тот же модуль может быть найден по разным путям,
+                // поэтому сравниваем канонические.
+                let канонический_путь = путь_к_файлу.canonicalize().unwrap_or_else(|_| путь_к_файлу.clone());
+                if let Some(начало) = self.стек_включений.iter().position(|(путь, _)| *путь == канонический_путь) {
+                    диагностика!(&имя.лок, "ОШИБКА", "Циклическое включение модуля «{имя}»", имя = имя.текст);
+                    for (_, включение) in &self.стек_включений[начало..] {
+                        диагностика!(&включение.лок, "ИНФО", "модуль «{имя}» включается здесь", имя = включение.текст);
+                    }
+                    return Err(());
+                }
+                if !self.включённые_модули.insert(канонический_путь.clone()) {
+                    return Ok(true);
+                }
+                self.имена.модули.insert(имя.текст.clone());
+                let содержимое = ::прочитать_содержимое_файла(&путь_к_файлу, Some(имя.лок.clone()))?;
+                let mut лекс = Лексер::новый(&путь_к_файлу, &содержимое);
+                self.стек_включений.push((канонический_путь, имя.clone()));
+                let результат = self.разобрать_лексемы(&mut лекс, определения, Some(&имя.текст));
+                self.стек_включений.pop();
+                результат?;
+            }
+            ВидЛексемы::КлючБибл => {
+                let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+                self.пп.библиотеки.insert(имя.текст.clone(), self.пп.библиотеки.len());
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
+            }
+            ВидЛексемы::КлючСтрукт => {
+                let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючНч])?;
+                let mut поля: Vec<(Лексема, Выражение)> = Vec::new();
+                // СДЕЛАТЬ: однополевые структуры через ключ «то»
+                while лекс.подсмотреть_лексему()?.вид != ВидЛексемы::КлючКц {
                     let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Двоеточие])?;
+                    let тип = Выражение::разобрать(лекс)?;
+                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
+                    поля.push((имя, тип));
+                }
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючКц])?;
+                определения.push(Определение::Структура{имя, поля});
+            }
+            ВидЛексемы::КлючПеречисление => {
+                let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючНч])?;
+                let mut значения: Vec<Лексема> = Vec::new();
+                while лекс.подсмотреть_лексему()?.вид != ВидЛексемы::КлючКц {
+                    let значение = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
                     let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
-                    let mut существует = false;
-                    for путь in пути_поиска_модулей {
-                        let mut путь_к_файлу = PathBuf::new();
-                        путь_к_файлу.push(путь);
-                        путь_к_файлу.push(&имя.текст);
-                        путь_к_файлу.set_extension("хуя");
-                        if путь_к_файлу.exists() {
-                            существует = true;
-                            let содержимое = ::прочитать_содержимое_файла(&путь_к_файлу, Some(имя.лок.clone()))?;
-                            let mut лекс = Лексер::новый(&путь_к_файлу, &содержимое);
-                            self.скомпилировать_лексемы(&mut лекс)?;
-                            break;
-                        }
-                    }
-                    if !существует {
-                        диагностика!(имя.лок, "ОШИБКА", "Не удалось найти модуль «{имя}».", имя = имя.текст);
+                    if let Some(дубль) = значения.iter().find(|дубль| дубль.текст == значение.текст) {
+                        диагностика!(&значение.лок, "ОШИБКА", "Значение «{имя}» уже существует", имя = значение.текст);
+                        диагностика!(&дубль.лок, "ИНФО", "Оно определено здесь");
                         return Err(());
                     }
+                    значения.push(значение);
                 }
-                ВидЛексемы::КлючБибл => {
-                    let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
-                    self.пп.библиотеки.insert(имя.текст.clone(), self.пп.библиотеки.len());
-                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючКц])?;
+                определения.push(Определение::Перечисление(Перечисление {имя, значения}));
+            }
+            ВидЛексемы::Конец => return Ok(false),
+            _ => unreachable!(),
+        }
+        Ok(true)
+    }
+
+    fn каталоги_поиска_модулей(&self) -> impl Iterator<Item = &Path> {
+        self.пути_поиска_модулей
+            .iter()
+            .map(|путь| путь.as_path())
+            .chain(КАТАЛОГИ_МОДУЛЕЙ_ПО_УМОЛЧАНИЮ.iter().map(Path::new))
+    }
+
+    fn найти_модуль(&self, имя: &str) -> Option<PathBuf> {
+        self.каталоги_поиска_модулей()
+            .map(|каталог| каталог.join(имя).with_extension("хуя"))
+            .find(|путь_к_файлу| путь_к_файлу.exists())
+    }
+
+    /// Второй проход. Сначала объявляем все типы, затем вычисляем
+    /// константы (они нужны для размеров массивов), раскладываем поля
+    /// структур, регистрируем сигнатуры процедур, выделяем глобальные
+    /// переменные и только после этого компилируем тела процедур.
+    ///
+    /// Константы, которые вызывают процедуры, вычисляются вместе с телами
+    /// процедур в порядке зависимостей между ними.
+    fn скомпилировать_определения(&mut self, определения: Vec<Определение>) -> Результат<()> {
+        let mut результат = Ok(());
+        let mut переменные = Vec::new();
+        let mut процедуры = Vec::new();
+        let mut порядок_констант = Vec::new();
+        let mut константы: HashMap<String, (Константа, Option<String>)> = HashMap::new();
+        let mut структуры = Vec::new();
+        for определение in определения {
+            match определение {
+                Определение::Перечисление(перечисление) => {
+                    if self.имена.верифицировать_переопределение_типа(&перечисление.имя).is_err() {
+                        результат = Err(());
+                        continue;
+                    }
+                    self.имена.перечисления.insert(перечисление.имя.текст.clone(), перечисление);
                 }
-                ВидЛексемы::КлючСтрукт => {
-                    let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
-                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючНч])?;
-                    let mut поля: HashMap<String, Поле> = HashMap::new();
-                    let mut размер = 0;
-                    // СДЕЛАТЬ: однополевые структуры через ключ «то»
-                    while лекс.подсмотреть_лексему()?.вид != ВидЛексемы::КлючКц {
-                        let смещение = размер;
-                        let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
-                        let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Двоеточие])?;
-                        let тип = скомпилировать_тип(&self.пп, &Выражение::разобрать(лекс)?, &self.имена, None)?;
-                        let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
-                        размер += выравнять_размер_по_слову(тип.размер(&self.имена.структуры));
-                        if let Some(дубль) = поля.get(&имя.текст) {
-                            диагностика!(&имя.лок, "ОШИБКА", "Поле «{имя}» уже существует", имя = имя.текст);
-                            диагностика!(&дубль.имя.лок, "ИНФО", "Оно определено здесь");
-                            return Err(());
-                        }
-                        if let Some(_) = поля.insert(имя.текст.clone(), Поле {имя, тип, смещение}) {
-                            unreachable!();
-                        }
+                Определение::Структура{имя, поля} => {
+                    if self.имена.верифицировать_переопределение_типа(&имя).is_err() {
+                        результат = Err(());
+                        continue;
                     }
-                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючКц])?;
-                    if let Some(дубль) = self.имена.структуры.get(&имя.текст) {
-                        диагностика!(&имя.лок, "ОШИБКА", "Структура «{имя}» уже существует", имя = имя.текст);
-                        диагностика!(&дубль.имя.лок, "ИНФО", "Она определено здесь");
-                        return Err(());
+                    // Поля и размер станут известны после вычисления констант.
+                    self.имена.структуры.insert(имя.текст.clone(), Структура {
+                        имя: имя.clone(),
+                        поля: HashMap::new(),
+                        размер: 0,
+                    });
+                    структуры.push((имя, поля));
+                }
+                Определение::Константа{константа, модуль} => {
+                    if let Some((дубль, _)) = константы.get(&константа.имя.текст) {
+                        диагностика!(&константа.имя.лок, "ОШИБКА",
+                                     "уже существует константа с именем «{имя}»",
+                                     имя = константа.имя.текст);
+                        диагностика!(&дубль.имя.лок, "ИНФО",
+                                     "она определена здесь. Выберите другое имя.");
+                        результат = Err(());
+                        continue;
                     }
-                    if let Some(_) = self.имена.структуры.insert(имя.текст.clone(), Структура {имя, поля, размер}) {
-                        unreachable!()
+                    порядок_констант.push(константа.имя.текст.clone());
+                    константы.insert(константа.имя.текст.clone(), (константа, модуль));
+                }
+                Определение::Переменная(переменная) => переменные.push(переменная),
+                Определение::Процедура{процедура, модуль, скрытая, ошибки_разбора} => процедуры.push((процедура, модуль, скрытая, ошибки_разбора)),
+            }
+        }
+
+        let имена_процедур: HashSet<String> = процедуры
+            .iter()
+            .map(|(процедура, модуль, скрытая, _)| ключ_процедуры(модуль, *скрытая, &процедура.имя.текст))
+            .collect();
+        let mut отложенные = ОтложенныеОпределения::default();
+        loop {
+            let вызывающие_процедуры: Vec<String> = константы
+                .iter()
+                .filter(|(_, (константа, модуль))| {
+                    let mut зависимости = Vec::new();
+                    собрать_идентификаторы(&константа.выражение, &mut зависимости);
+                    зависимости.iter().any(|зависимость| {
+                        отложенные.константы.contains_key(&зависимость.текст) ||
+                            ключ_вызова(модуль, &зависимость.текст, |ключ| имена_процедур.contains(ключ)).is_some()
+                    })
+                })
+                .map(|(имя, _)| имя.clone())
+                .collect();
+            if вызывающие_процедуры.is_empty() {
+                break;
+            }
+            for имя in вызывающие_процедуры {
+                let константа = константы.remove(&имя).unwrap();
+                отложенные.константы.insert(имя, константа);
+            }
+        }
+        let порядок_отложенных_констант: Vec<String> = порядок_констант
+            .iter()
+            .filter(|имя| отложенные.константы.contains_key(*имя))
+            .cloned()
+            .collect();
+
+        let mut в_процессе = Vec::new();
+        for имя in порядок_констант {
+            if отложенные.константы.contains_key(&имя) {
+                continue;
+            }
+            if self.скомпилировать_константу(&имя, &mut константы, &mut в_процессе).is_err() {
+                результат = Err(());
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+            }
+        }
+
+        let mut порядок_структур = Vec::new();
+        let mut поля_структур: HashMap<String, (Лексема, Vec<(Лексема, Тип)>)> = HashMap::new();
+        for (имя, поля) in структуры {
+            let mut скомп_поля = Vec::new();
+            let mut ошибка = false;
+            for (имя_поля, тип) in поля {
+                match скомпилировать_тип(&self.пп, &тип, &self.имена, None) {
+                    Ok(тип) => скомп_поля.push((имя_поля, тип)),
+                    Err(()) => {
+                        пояснить_отложенные_константы(&отложенные, [&тип]);
+                        ошибка = true;
                     }
                 }
-                ВидЛексемы::Конец => break,
-                _ => unreachable!(),
             }
+            if ошибка {
+                результат = Err(());
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+                continue;
+            }
+            порядок_структур.push(имя.текст.clone());
+            поля_структур.insert(имя.текст.clone(), (имя, скомп_поля));
+        }
+        let mut в_процессе = Vec::new();
+        for имя in порядок_структур {
+            if self.разместить_структуру(&имя, &mut поля_структур, &mut в_процессе).is_err() {
+                результат = Err(());
+            }
+        }
+
+        let mut порядок_тел = Vec::new();
+        for (процедура, модуль, скрытая, ошибки_разбора) in процедуры {
+            let ключ = ключ_процедуры(&модуль, скрытая, &процедура.имя.текст);
+            let проверка = if ключ != процедура.имя.текст {
+                self.имена.верифицировать_переопределение_скрытой_процедуры(&процедура.имя, &ключ)
+            } else {
+                self.имена.верифицировать_переопределение_имени(&процедура.имя)
+            };
+            if проверка.is_err() {
+                результат = Err(());
+                continue;
+            }
+            match зарегистрировать_процедуру(&mut self.пп, &mut self.имена, &процедура, модуль.clone(), скрытая) {
+                Ok(размер_параметров_на_стеке) => if !ошибки_разбора {
+                    порядок_тел.push(ключ.clone());
+                    отложенные.тела.insert(ключ, (процедура, модуль, размер_параметров_на_стеке));
+                }
+                Err(()) => {
+                    let типы = процедура.параметры.iter().map(|параметр| &параметр.тип).chain(процедура.тип_результата.iter());
+                    пояснить_отложенные_константы(&отложенные, типы);
+                    результат = Err(());
+                    if слишком_много_ошибок() {
+                        return Err(());
+                    }
+                }
+            }
+        }
+
+        // Начальные значения глобальных переменных могут вызывать процедуры,
+        // поэтому сигнатуры процедур регистрируем раньше.
+        let mut инициализации = Vec::new();
+        for переменная in переменные {
+            let Some(тип) = переменная.тип.as_ref().or(переменная.значение.as_ref()).cloned() else {
+                unreachable!("У переменной без типа всегда есть начальное значение. Это проверяется на этапе разбора");
+            };
+            if self.скомпилировать_глобальную_переменную(переменная, &mut инициализации).is_err() {
+                пояснить_отложенные_константы(&отложенные, [&тип]);
+                результат = Err(());
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+            }
+        }
+
+        for ключ in порядок_тел {
+            if self.скомпилировать_тело(&ключ, &mut отложенные).is_err() {
+                результат = Err(());
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+            }
+        }
+        // Константы, которые не использует ни одна процедура.
+        for имя in порядок_отложенных_констант {
+            if self.вычислить_отложенную_константу(&имя, &mut отложенные).is_err() {
+                результат = Err(());
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+            }
+        }
+        if self.скомпилировать_инициализацию(инициализации).is_err() {
+            результат = Err(());
+        }
+        результат
+    }
+
+    /// Перед компиляцией тела вычисляем константы, которые оно использует,
+    /// и компилируем процедуры, которые вызываются в его локальных
+    /// константах.
+    fn скомпилировать_тело(&mut self, ключ: &str, отложенные: &mut ОтложенныеОпределения) -> Результат<()> {
+        if отложенные.неудачные.contains(ключ) {
+            return Err(());
+        }
+        if let Some(начало) = отложенные.в_процессе.iter().position(|(в_процессе, _)| в_процессе == ключ) {
+            self.сообщить_о_циклической_зависимости(&отложенные.в_процессе[начало..]);
+            return Err(());
+        }
+        let Some((процедура, модуль, размер_параметров_на_стеке)) = отложенные.тела.remove(ключ) else {
+            // Тело уже скомпилировано, либо процедура внешняя. Иначе тело
+            // не удалось разобрать, и об этом уже сообщено.
+            return match self.имена.процедуры.get(ключ) {
+                Some(СкомпПроцедура{точка_входа: ТочкаВхода::Внутреняя{адрес: АДРЕС_НЕИЗВЕСТЕН}, ..}) => Err(()),
+                _ => Ok(()),
+            };
+        };
+
+        let mut результат = Ok(());
+        let mut идентификаторы = Vec::new();
+        let mut вычисляемые = Vec::new();
+        if let ТелоПроцедуры::Внутренее{блок, ..} = &процедура.тело {
+            собрать_идентификаторы_блока(блок, &mut идентификаторы, &mut вычисляемые);
+        }
+        отложенные.в_процессе.push((ключ.to_string(), процедура.имя.clone()));
+        for идентификатор in идентификаторы.iter().chain(вычисляемые.iter()) {
+            if self.это_отложенная_константа(&идентификатор.текст, отложенные) {
+                if self.вычислить_отложенную_константу(&идентификатор.текст, отложенные).is_err() {
+                    результат = Err(());
+                }
+            }
+        }
+        for идентификатор in &вычисляемые {
+            if let Some(вызов) = ключ_вызова(&модуль, &идентификатор.текст, |ключ| self.имена.процедуры.contains_key(ключ)) {
+                if self.скомпилировать_вызываемые_процедуры(&вызов, отложенные).is_err() {
+                    результат = Err(());
+                }
+            }
+        }
+        отложенные.в_процессе.pop();
+
+        if результат.is_ok() {
+            результат = скомпилировать_процедуру(&mut self.пп, &mut self.имена, &процедура, ключ, размер_параметров_на_стеке);
+        }
+        if результат.is_err() {
+            отложенные.неудачные.insert(ключ.to_string());
+            return Err(());
+        }
+        let вызовы = идентификаторы
+            .iter()
+            .chain(вычисляемые.iter())
+            .filter_map(|идентификатор| ключ_вызова(&модуль, &идентификатор.текст, |ключ| self.имена.процедуры.contains_key(ключ)))
+            .collect();
+        отложенные.вызовы.insert(ключ.to_string(), вызовы);
+        Ok(())
+    }
+
+    /// Во время вычисления константы процедура может вызвать любую другую,
+    /// до которой дотягивается по вызовам. Все они должны быть
+    /// скомпилированы.
+    fn скомпилировать_вызываемые_процедуры(&mut self, ключ: &str, отложенные: &mut ОтложенныеОпределения) -> Результат<()> {
+        let mut очередь = vec![ключ.to_string()];
+        let mut посещённые = HashSet::new();
+        while let Some(ключ) = очередь.pop() {
+            if !посещённые.insert(ключ.clone()) {
+                continue;
+            }
+            self.скомпилировать_тело(&ключ, отложенные)?;
+            if let Some(вызовы) = отложенные.вызовы.get(&ключ) {
+                очередь.extend(вызовы.iter().cloned());
+            }
+        }
+        Ok(())
+    }
+
+    fn это_отложенная_константа(&self, имя: &str, отложенные: &ОтложенныеОпределения) -> bool {
+        отложенные.константы.contains_key(имя) ||
+            (!self.имена.процедуры.contains_key(имя) && отложенные.в_процессе.iter().any(|(в_процессе, _)| в_процессе == имя))
+    }
+
+    fn вычислить_отложенную_константу(&mut self, имя: &str, отложенные: &mut ОтложенныеОпределения) -> Результат<()> {
+        if self.имена.константы.contains_key(имя) {
+            return Ok(());
+        }
+        if отложенные.неудачные.contains(имя) {
+            return Err(());
+        }
+        if let Some(начало) = отложенные.в_процессе.iter().position(|(в_процессе, _)| в_процессе == имя) {
+            self.сообщить_о_циклической_зависимости(&отложенные.в_процессе[начало..]);
+            return Err(());
+        }
+        let Some((константа, модуль)) = отложенные.константы.remove(имя) else {
+            return Err(());
+        };
+
+        let mut результат = Ok(());
+        let mut зависимости = Vec::new();
+        собрать_идентификаторы(&константа.выражение, &mut зависимости);
+        отложенные.в_процессе.push((имя.to_string(), константа.имя.clone()));
+        for зависимость in зависимости {
+            let успех = if self.это_отложенная_константа(&зависимость.текст, отложенные) {
+                self.вычислить_отложенную_константу(&зависимость.текст, отложенные)
+            } else if let Some(вызов) = ключ_вызова(&модуль, &зависимость.текст, |ключ| self.имена.процедуры.contains_key(ключ)) {
+                self.скомпилировать_вызываемые_процедуры(&вызов, отложенные)
+            } else {
+                Ok(())
+            };
+            if успех.is_err() {
+                результат = Err(());
+            }
+        }
+        отложенные.в_процессе.pop();
+
+        if результат.is_ok() {
+            результат = self.определить_константу(константа, модуль);
+        }
+        if результат.is_err() {
+            отложенные.неудачные.insert(имя.to_string());
+        }
+        результат
+    }
+
+    fn сообщить_о_циклической_зависимости(&self, цикл: &[(String, Лексема)]) {
+        let (ключ, имя) = &цикл[0];
+        if self.имена.процедуры.contains_key(ключ) {
+            диагностика!(&имя.лок, "ОШИБКА", "Процедура «{имя}» вызывается при вычислении константы, которая нужна ей самой", имя = имя.текст);
+        } else {
+            диагностика!(&имя.лок, "ОШИБКА", "Константа «{имя}» зависит сама от себя", имя = имя.текст);
+        }
+        for (ключ, имя) in &цикл[1..] {
+            if self.имена.процедуры.contains_key(ключ) {
+                диагностика!(&имя.лок, "ИНФО", "через процедуру «{имя}»", имя = имя.текст);
+            } else {
+                диагностика!(&имя.лок, "ИНФО", "через константу «{имя}»", имя = имя.текст);
+            }
+        }
+    }
+
+    /// Константы вычисляются в порядке зависимостей между ними, а не в
+    /// порядке определения.
+    fn скомпилировать_константу(&mut self, имя: &str, константы: &mut HashMap<String, (Константа, Option<String>)>, в_процессе: &mut Vec<Лексема>) -> Результат<()> {
+        if self.имена.константы.contains_key(имя) {
+            return Ok(());
+        }
+        if let Some(начало) = в_процессе.iter().position(|константа| константа.текст == имя) {
+            диагностика!(&в_процессе[начало].лок, "ОШИБКА", "Константа «{имя}» зависит сама от себя");
+            for константа in &в_процессе[начало + 1..] {
+                диагностика!(&константа.лок, "ИНФО", "через константу «{имя}»", имя = константа.текст);
+            }
+            return Err(());
+        }
+        // Константы нет ни среди скомпилированных, ни среди ожидающих.
+        // Значит её уже не удалось скомпилировать, и об этом уже сообщено.
+        let Some((константа, модуль)) = константы.remove(имя) else {
+            return Err(());
+        };
+
+        в_процессе.push(константа.имя.clone());
+        let mut зависимости = Vec::new();
+        собрать_идентификаторы(&константа.выражение, &mut зависимости);
+        let mut результат = Ok(());
+        for зависимость in зависимости {
+            if константы.contains_key(&зависимость.текст) || в_процессе.iter().any(|константа| константа.текст == зависимость.текст) {
+                if self.скомпилировать_константу(&зависимость.текст, константы, в_процессе).is_err() {
+                    результат = Err(());
+                }
+            }
+        }
+        в_процессе.pop();
+        результат?;
+        self.определить_константу(константа, модуль)
+    }
+
+    fn определить_константу(&mut self, константа: Константа, модуль: Option<String>) -> Результат<()> {
+        self.имена.верифицировать_переопределение_имени(&константа.имя)?;
+        проверить_литерал_по_умолчанию(&константа.выражение, &Тип::Цел64)?;
+        let значение = скомпилировать_константное_выражение(&self.пп, &self.имена, None, &константа.выражение)?;
+        if let Some(_) = self.имена.константы.insert(константа.имя.текст.clone(), разместить_константу(&mut self.пп, константа.имя.clone(), модуль, значение)) {
+            unreachable!("Проверка переопределения констант должна происходить на этапе разбора")
+        }
+        Ok(())
+    }
+
+    /// Размер структуры зависит от размеров структур, которые она содержит
+    /// по значению, поэтому раскладываем их первыми.
+    fn разместить_структуру(&mut self, имя: &str, поля_структур: &mut HashMap<String, (Лексема, Vec<(Лексема, Тип)>)>, в_процессе: &mut Vec<Лексема>) -> Результат<()> {
+        if let Some(начало) = в_процессе.iter().position(|структура| структура.текст == имя) {
+            диагностика!(&в_процессе[начало].лок, "ОШИБКА", "Структура «{имя}» содержит саму себя");
+            for структура in &в_процессе[начало + 1..] {
+                диагностика!(&структура.лок, "ИНФО", "через структуру «{имя}»", имя = структура.текст);
+            }
+            диагностика!(&в_процессе[начало].лок, "ИНФО", "Используйте указатель или срез, чтобы разорвать цикл.");
+            return Err(());
         }
+        // Структура уже разложена, либо её поля не удалось скомпилировать.
+        let Some((имя_структуры, поля)) = поля_структур.remove(имя) else {
+            return Ok(());
+        };
 
+        в_процессе.push(имя_структуры.clone());
+        let mut зависимости = Vec::new();
+        for (_, тип) in &поля {
+            собрать_структуры_по_значению(тип, &mut зависимости);
+        }
+        let mut результат = Ok(());
+        for зависимость in зависимости {
+            if self.разместить_структуру(зависимость, поля_структур, в_процессе).is_err() {
+                результат = Err(());
+            }
+        }
+        в_процессе.pop();
+        результат?;
+
+        let mut скомп_поля: HashMap<String, Поле> = HashMap::new();
+        let mut размер = 0;
+        for (имя, тип) in поля {
+            let смещение = размер;
+            размер += выравнять_размер_по_слову(тип.размер(&self.имена.структуры));
+            if let Some(дубль) = скомп_поля.get(&имя.текст) {
+                диагностика!(&имя.лок, "ОШИБКА", "Поле «{имя}» уже существует", имя = имя.текст);
+                диагностика!(&дубль.имя.лок, "ИНФО", "Оно определено здесь");
+                return Err(());
+            }
+            if let Some(_) = скомп_поля.insert(имя.текст.clone(), Поле {имя, тип, смещение}) {
+                unreachable!();
+            }
+        }
+        self.имена.структуры.insert(имя_структуры.текст.clone(), Структура {имя: имя_структуры, поля: скомп_поля, размер});
         Ok(())
     }
 
+    /// Переменные с константным начальным значением сразу кладём в
+    /// инициализированные данные. Остальные начальные значения вычисляются
+    /// при запуске программы, см. скомпилировать_инициализацию.
+    fn скомпилировать_глобальную_переменную(&mut self, синтаксис: Переменная, инициализации: &mut Vec<(Лексема, Выражение)>) -> Результат<()> {
+        let имя = синтаксис.имя;
+        self.имена.верифицировать_переопределение_имени(&имя)?;
+        let явный_тип = match &синтаксис.тип {
+            Some(тип) => Some(скомпилировать_тип(&self.пп, тип, &self.имена, None)?),
+            None => None,
+        };
+        let mut результат = Ok(());
+        let тип = match синтаксис.значение {
+            Some(значение) if self.вычислимо_при_компиляции(&значение) => {
+                let значение_константы = скомпилировать_константное_выражение(&self.пп, &self.имена, None, &значение)
+                    .and_then(|mut значение_константы| {
+                        if let Some(тип) = &явный_тип {
+                            значение_константы.тип = уточнить_тип_литерала(&значение, значение_константы.тип, тип)?;
+                            if let (Тип::Вещ32, Тип::Вещ64, Some(число)) = (&значение_константы.тип, тип, значение_вещественного_литерала(&значение)) {
+                                значение_константы.тип = Тип::Вещ64;
+                                значение_константы.байты = число.to_le_bytes().to_vec();
+                            }
+                            проверить_типы(значение.лок(), тип, &значение_константы.тип)?;
+                            // Литерал вычислялся как «цел64», а переменная
+                            // может быть уже.
+                            значение_константы.байты.truncate(тип.размер(&self.имена.структуры));
+                        } else {
+                            проверить_выведенный_тип(&значение, &значение_константы.тип)?;
+                        }
+                        Ok(значение_константы)
+                    });
+                match (значение_константы, явный_тип) {
+                    (Ok(значение_константы), явный_тип) => {
+                        let тип = явный_тип.unwrap_or_else(|| значение_константы.тип.clone());
+                        let смещение = разместить_значение(&mut self.пп, значение_константы) as i32;
+                        if let Some(_) = self.имена.переменные.insert(имя.текст.clone(), СкомпПеременная {имя, тип, смещение, ссылка: false, в_иниц_данных: true, только_чтение: false}) {
+                            unreachable!("Проверка переопределения переменных должна происходить на этапе разбора")
+                        }
+                        return Ok(());
+                    }
+                    // Даже если начальное значение не скомпилировалось,
+                    // переменную с явным типом всё равно объявляем, чтобы не
+                    // сыпать ошибками о неизвестном имени.
+                    (Err(()), Some(тип)) => {
+                        результат = Err(());
+                        тип
+                    }
+                    (Err(()), None) => return Err(()),
+                }
+            }
+            Some(значение) => {
+                let тип = match явный_тип {
+                    Some(тип) => тип,
+                    None => {
+                        let тип = self.тип_выражения(&значение)?;
+                        проверить_выведенный_тип(&значение, &тип)?;
+                        тип
+                    }
+                };
+                инициализации.push((имя.clone(), значение));
+                тип
+            }
+            None => явный_тип.expect("У переменной без типа всегда есть начальное значение. Это проверяется на этапе разбора"),
+        };
+        let смещение = self.пп.размер_неиниц_данных as i32;
+        self.пп.размер_неиниц_данных += тип.размер(&self.имена.структуры);
+        if let Some(_) = self.имена.переменные.insert(имя.текст.clone(), СкомпПеременная {имя, тип, смещение, ссылка: false, в_иниц_данных: false, только_чтение: false}) {
+            unreachable!("Проверка переопределения переменных должна происходить на этапе разбора")
+        }
+        результат
+    }
+
+    /// Тип выражения вне процедур. Код компилируется во временную копию ПП
+    /// и выбрасывается.
+    fn тип_выражения(&self, выражение: &Выражение) -> Результат<Тип> {
+        let mut пп = self.пп.clone();
+        let процедура = служебная_процедура("<Начальное значение>", выражение.лок());
+        let mut временный_размер = 0;
+        скомпилировать_выражение(&mut пп, &self.имена, &процедура, &ЛокальныеИмена::default(), выражение, &mut временный_размер)
+    }
+
+    /// Начальное значение, которое зависит только от литералов и уже
+    /// вычисленных констант.
+    fn вычислимо_при_компиляции(&self, выражение: &Выражение) -> bool {
+        let mut идентификаторы = Vec::new();
+        собрать_идентификаторы(выражение, &mut идентификаторы);
+        идентификаторы.iter().all(|идентификатор| {
+            self.имена.константы.contains_key(&идентификатор.текст) ||
+                self.имена.перечисления.contains_key(&идентификатор.текст)
+        })
+    }
+
+    /// Начальные значения глобальных переменных, которые нельзя вычислить
+    /// во время компиляции, присваиваются отдельным кодом в порядке
+    /// определения переменных. Он выполняется перед точкой входа.
+    fn скомпилировать_инициализацию(&mut self, инициализации: Vec<(Лексема, Выражение)>) -> Результат<()> {
+        if инициализации.is_empty() {
+            return Ok(());
+        }
+        let точка_инициализации = self.пп.код.len();
+        let процедура = служебная_процедура("<Инициализация глобальных переменных>", &инициализации[0].0.лок);
+        let локальные_имена = ЛокальныеИмена::default();
+        let mut результат = Ok(());
+        for (имя, значение) in инициализации {
+            let переменная = self.имена.переменные.get(&имя.текст).expect("Переменная должна быть уже объявлена").clone();
+            let mut временный_размер = 0;
+            let инициализация: Результат<()> = (|| {
+                let тип_значения = скомпилировать_выражение(&mut self.пп, &self.имена, &процедура, &локальные_имена, &значение, &mut временный_размер)?;
+                проверить_тип_значения(&mut self.пп, &значение, &переменная.тип, тип_значения)?;
+                Локация::Глобальная.протолкнуть_смещение(&mut self.пп, &имя.лок, переменная.смещение);
+                записать_тип(&имя.лок, &mut self.пп.код, &self.имена.структуры, &переменная.тип)?;
+                if временный_размер > 0 {
+                    self.пп.код.push(Инструкция{ вид: ВидИнструкции::ОсвободитьСоСтека(временный_размер as u64), лок: имя.лок.clone() });
+                }
+                Ok(())
+            })();
+            if инициализация.is_err() {
+                результат = Err(());
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+            }
+        }
+        self.пп.код.push(Инструкция{ вид: ВидИнструкции::Возврат, лок: процедура.имя.лок.clone() });
+        self.пп.точка_инициализации = Some(точка_инициализации);
+        результат
+    }
+
     pub fn завершить_компиляцию(&mut self) {
-        for (_, СкомпПеременная{смещение, ..}) in self.имена.переменные.iter_mut() {
-            *смещение += self.пп.иниц_данные.len() as i32;
+        for (_, СкомпПеременная{смещение, в_иниц_данных, ..}) in self.имена.переменные.iter_mut() {
+            if !*в_иниц_данных {
+                *смещение += self.пп.иниц_данные.len() as i32;
+            }
         }
 
         for индекс in &self.пп.заплатки_неиниц_указателей {
@@ -2485,5 +5052,16 @@ impl Программа {
                 unreachable!("Ошибка в процессе сбора заплаток указателей на неинициализированные данные. Каждый индекс такой заплатки должен указывать на инструкцию ПротолкнутьУказатель");
             }
         }
+
+        for (индекс, имя) in &self.пп.заплатки_вызовов {
+            let Some(СкомпПроцедура{точка_входа: ТочкаВхода::Внутреняя{адрес}, ..}) = self.имена.процедуры.get(имя) else {
+                unreachable!("Заплатка вызова должна ссылаться на внутреннюю процедуру");
+            };
+            if let Some(Инструкция{ вид: ВидИнструкции::ВнутреннийВызов(цель), .. }) = self.пп.код.get_mut(*индекс) {
+                *цель = *адрес;
+            } else {
+                unreachable!("Ошибка в процессе сбора заплаток вызовов. Каждый индекс такой заплатки должен указывать на инструкцию ВнутреннийВызов");
+            }
+        }
     }
 }
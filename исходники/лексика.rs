@@ -23,6 +23,8 @@ pub const ПРИСТАВКИ_ПРЕПИНАНИЙ: &[(&[char], ВидЛексе
     (&['='], ВидЛексемы::Равно),
     (&['!', '='], ВидЛексемы::НеРавно),
     (&['!'], ВидЛексемы::Не),
+    (&['@'], ВидЛексемы::Собака),
+    (&['^'], ВидЛексемы::Крышка),
 ];
 
 pub const КЛЮЧЕВЫЕ_СЛОВА: &[(&str, ВидЛексемы)] = &[
@@ -45,6 +47,7 @@ pub const КЛЮЧЕВЫЕ_СЛОВА: &[(&str, ВидЛексемы)] = &[
     ("внешняя", ВидЛексемы::КлючВнешняя),
     ("библ", ВидЛексемы::КлючБибл),
     ("структ", ВидЛексемы::КлючСтрукт),
+    ("перечисление", ВидЛексемы::КлючПеречисление),
     ("истина", ВидЛексемы::КлючИстина),
     ("ложь", ВидЛексемы::КлючЛожь),
     ("лбс", ВидЛексемы::КлючЛбс),
@@ -53,6 +56,10 @@ pub const КЛЮЧЕВЫЕ_СЛОВА: &[(&str, ВидЛексемы)] = &[
     ("вилка", ВидЛексемы::КлючВилка),
     ("когда", ВидЛексемы::КлючКогда),
     ("любое", ВидЛексемы::КлючЛюбое),
+    ("прервать", ВидЛексемы::КлючПрервать),
+    ("продолжить", ВидЛексемы::КлючПродолжить),
+    ("пусто", ВидЛексемы::КлючПусто),
+    ("скрыт", ВидЛексемы::КлючСкрыт),
     // СДЕЛАТЬ: оператор «мод».
 ];
 
@@ -80,6 +87,7 @@ pub enum ВидЛексемы {
     КлючВнешняя,
     КлючБибл,
     КлючСтрукт,
+    КлючПеречисление,
     КлючИстина,
     КлючЛожь,
     КлючЛбс,
@@ -88,6 +96,10 @@ pub enum ВидЛексемы {
     КлючВилка,
     КлючКогда,
     КлючЛюбое,
+    КлючПрервать,
+    КлючПродолжить,
+    КлючПусто,
+    КлючСкрыт,
 
     ОткрытаяСкобка,
     ЗакрытаяСкобка,
@@ -108,10 +120,13 @@ pub enum ВидЛексемы {
     БольшеРавно,
     Не,
     НеРавно,
+    Собака,
+    Крышка,
 
     ЦелЧисло,
-    ЦелШестЧисло,
+    ЦелЧислоПоОснованию,
     ВещЧисло,
+    Символ,
     Строка,
 }
 
@@ -141,6 +156,7 @@ impl ВидЛексемы {
             ВидЛексемы::КлючВнешняя        => Сущ{текст: "«внешняя»",                  род: Род::Жен},
             ВидЛексемы::КлючБибл           => Сущ{текст: "«библ»",                     род: Род::Муж},
             ВидЛексемы::КлючСтрукт         => Сущ{текст: "«структ»",                   род: Род::Муж},
+            ВидЛексемы::КлючПеречисление   => Сущ{текст: "«перечисление»",             род: Род::Сред},
             ВидЛексемы::КлючИстина         => Сущ{текст: "«истина»",                   род: Род::Жен},
             ВидЛексемы::КлючЛожь           => Сущ{текст: "«ложь»",                     род: Род::Жен},
             ВидЛексемы::КлючЛбс            => Сущ{текст: "«лбс»",                      род: Род::Муж},
@@ -149,6 +165,10 @@ impl ВидЛексемы {
             ВидЛексемы::КлючВилка          => Сущ{текст: "«вилка»",                    род: Род::Жен},
             ВидЛексемы::КлючКогда          => Сущ{текст: "«когда»",                    род: Род::Сред},
             ВидЛексемы::КлючЛюбое          => Сущ{текст: "«любое»",                    род: Род::Сред},
+            ВидЛексемы::КлючПрервать       => Сущ{текст: "«прервать»",                 род: Род::Сред},
+            ВидЛексемы::КлючПродолжить     => Сущ{текст: "«продолжить»",               род: Род::Сред},
+            ВидЛексемы::КлючПусто          => Сущ{текст: "«пусто»",                    род: Род::Сред},
+            ВидЛексемы::КлючСкрыт          => Сущ{текст: "«скрыт»",                    род: Род::Сред},
 
             // Знаки препинания
             ВидЛексемы::ОткрытаяСкобка     => Сущ{текст: "открытая скобка",            род: Род::Жен},
@@ -170,10 +190,13 @@ impl ВидЛексемы {
             ВидЛексемы::БольшеРавно        => Сущ{текст: "больше либо равно",          род: Род::Сред},
             ВидЛексемы::Не                 => Сущ{текст: "отрицание",                  род: Род::Сред},
             ВидЛексемы::НеРавно            => Сущ{текст: "не равно",                   род: Род::Сред},
+            ВидЛексемы::Собака             => Сущ{текст: "собака",                     род: Род::Жен},
+            ВидЛексемы::Крышка             => Сущ{текст: "крышка",                     род: Род::Жен},
 
-            ВидЛексемы::ЦелШестЧисло       => Сущ{текст: "целое шестнадцатеричное число", род: Род::Сред},
             ВидЛексемы::ЦелЧисло           => Сущ{текст: "целое число",                род: Род::Сред},
+            ВидЛексемы::ЦелЧислоПоОснованию => Сущ{текст: "целое число по основанию",  род: Род::Сред},
             ВидЛексемы::ВещЧисло           => Сущ{текст: "вещественное число",         род: Род::Сред},
+            ВидЛексемы::Символ             => Сущ{текст: "символ",                     род: Род::Муж},
             ВидЛексемы::Строка             => Сущ{текст: "строка",                     род: Род::Жен},
         }
     }
@@ -192,12 +215,15 @@ pub struct Лексер<'ы> {
     pub позиция: usize,
     pub начало_строки: usize,
     pub строка: usize,
-    pub буфер: Option<Результат<Лексема>>
+    pub буфер: Vec<Результат<Лексема>>
 }
 
-pub fn шестнадцатеричная_цифра(знак: &char) -> Option<i64> {
-    if знак.is_digit(10) {
-        Some(*знак as i64 - '0' as i64)
+/// Значение цифры в системе счисления с основанием до 36. Цифры больше
+/// девяти записываются кириллическими буквами в порядке латинского
+/// алфавита: А=10, Б=11, Ц=12, Д=13, Е=14, Ф=15 и так далее до З=35.
+pub fn значение_цифры(знак: &char) -> Option<u64> {
+    if знак.is_ascii_digit() {
+        Some(*знак as u64 - '0' as u64)
     } else {
         match знак {
             'А' | 'а' => Some(10),
@@ -206,11 +232,59 @@ pub fn шестнадцатеричная_цифра(знак: &char) -> Option<
             'Д' | 'д' => Some(13),
             'Е' | 'е' => Some(14),
             'Ф' | 'ф' => Some(15),
+            'Г' | 'г' => Some(16),
+            'Х' | 'х' => Some(17),
+            'И' | 'и' => Some(18),
+            'Й' | 'й' => Some(19),
+            'К' | 'к' => Some(20),
+            'Л' | 'л' => Some(21),
+            'М' | 'м' => Some(22),
+            'Н' | 'н' => Some(23),
+            'О' | 'о' => Some(24),
+            'П' | 'п' => Some(25),
+            'Ч' | 'ч' => Some(26),
+            'Р' | 'р' => Some(27),
+            'С' | 'с' => Some(28),
+            'Т' | 'т' => Some(29),
+            'У' | 'у' => Some(30),
+            'В' | 'в' => Some(31),
+            'Ш' | 'ш' => Some(32),
+            'Ж' | 'ж' => Some(33),
+            'Ы' | 'ы' => Some(34),
+            'З' | 'з' => Some(35),
             _ => None,
         }
     }
 }
 
+/// Суффиксы типа, которые могут стоять сразу после числа.
+const СУФФИКСЫ_ЧИСЕЛ: &[&str] = &["нат", "вещ64"];
+
+/// Значение целого литерала: десятичного или вида «основание%цифры».
+/// Разделители «_» пропускаются. Возвращает None, если значение не
+/// помещается в 64 бита. Цифры должны быть уже проверены лексером.
+pub fn значение_целого(текст: &str) -> Option<u64> {
+    let (основание, цифры) = match текст.split_once('%') {
+        Some((основание, цифры)) => (значение_целого(основание)?, цифры),
+        None => (10, текст),
+    };
+    let mut число: u64 = 0;
+    for знак in цифры.chars().filter(|знак| *знак != '_') {
+        let цифра = значение_цифры(&знак).expect("Все цифры должны быть проверены на этапе лексического анализа");
+        число = число.checked_mul(основание)?.checked_add(цифра)?;
+    }
+    Some(число)
+}
+
+/// Текст вещественного литерала в виде, понятном str::parse: без
+/// разделителей «_» и с латинской «e» перед порядком.
+pub fn текст_вещественного(текст: &str) -> String {
+    текст.chars()
+        .filter(|знак| *знак != '_')
+        .map(|знак| if знак == 'е' || знак == 'Е' { 'e' } else { знак })
+        .collect()
+}
+
 impl<'a> Лексер<'a> {
     pub fn новый(путь_к_файлу: &'a Path, символы: &'a [char]) -> Лексер<'a> {
         Лексер {
@@ -219,7 +293,7 @@ impl<'a> Лексер<'a> {
             позиция: 0,
             начало_строки: 0,
             строка: 0,
-            буфер: None,
+            буфер: Vec::new(),
         }
     }
 
@@ -266,6 +340,117 @@ impl<'a> Лексер<'a> {
         }
     }
 
+    /// Суффикс типа, который начинается с данной позиции и за которым не
+    /// следует продолжение идентификатора.
+    fn суффикс_числа(&self, позиция: usize) -> Option<&'static str> {
+        СУФФИКСЫ_ЧИСЕЛ.iter().copied().find(|суффикс| {
+            let конец = позиция + суффикс.chars().count();
+            self.символы.get(позиция..конец).map(|символы| символы.iter().copied().eq(суффикс.chars())).unwrap_or(false) &&
+                !self.символы.get(конец).map(|сим| сим.is_alphanumeric() || *сим == '_').unwrap_or(false)
+        })
+    }
+
+    /// Отрезает цифры по данному основанию вместе с разделителями «_».
+    /// Возвращает количество отрезанных цифр. В больших основаниях буквы
+    /// суффикса сами являются цифрами, поэтому «_» перед суффиксом
+    /// заканчивает число: «30%1_нат».
+    fn отрезать_цифры(&mut self, основание: u64) -> usize {
+        let mut количество = 0;
+        while let Some(&сим) = self.текущий_символ() {
+            if сим == '_' {
+                self.отрезать_символ();
+                if self.суффикс_числа(self.позиция).is_some() {
+                    break;
+                }
+            } else if значение_цифры(&сим).map(|цифра| цифра < основание).unwrap_or(false) {
+                self.отрезать_символ();
+                количество += 1;
+            } else {
+                break;
+            }
+        }
+        количество
+    }
+
+    /// Начинается ли порядок вещественного числа: «е», за которой идут
+    /// цифры, возможно со знаком. Иначе «е» может быть началом
+    /// суффикса типа.
+    fn имеет_порядок(&self) -> bool {
+        let Some('е' | 'Е') = self.текущий_символ() else {
+            return false;
+        };
+        let mut позиция = self.позиция + 1;
+        if let Some('+' | '-') = self.символы.get(позиция) {
+            позиция += 1;
+        }
+        self.символы.get(позиция).map(|сим| сим.is_ascii_digit()).unwrap_or(false)
+    }
+
+    /// Разбирает литерал вида «основание%цифры». Лексер стоит на «%»,
+    /// основание начинается с позиции «начало».
+    fn целое_по_основанию(&mut self, начало: usize, лок: Лок) -> Результат<Лексема> {
+        let текст_основания: String = self.символы[начало..self.позиция].iter().collect();
+        let основание = match значение_целого(&текст_основания) {
+            Some(основание) if 2 <= основание && основание <= 36 => основание,
+            _ => {
+                self.отрезать_символ();
+                self.отрезать_цифры(36);
+                диагностика!(&лок, "ОШИБКА", "Основание системы счисления должно быть от 2 до 36, а не {текст_основания}");
+                return Err(());
+            }
+        };
+        self.отрезать_символ();
+        let начало_цифр = self.позиция;
+        let лок_цифр = self.лок();
+        let количество_цифр = self.отрезать_цифры(основание);
+        if let Some(сим) = self.текущий_символ().cloned() {
+            // Буква сразу после цифр может оказаться суффиксом типа,
+            // например «16%ФФнат». Остальные цифры, не влезающие в
+            // основание, точно ошибка.
+            if значение_цифры(&сим).is_some() && self.суффикс_числа(self.позиция).is_none() {
+                диагностика!(&self.лок(), "ОШИБКА", "Цифра «{сим}» недопустима в системе счисления по основанию {основание}");
+                self.отрезать_цифры(36);
+                return Err(());
+            }
+        }
+        if количество_цифр == 0 {
+            диагностика!(&лок_цифр, "ОШИБКА", "Нет цифр после «{текст_основания}%»");
+            return Err(());
+        }
+        // В основаниях от 24 буквы суффикса тоже являются цифрами и
+        // поглощаются числом. Молча читать «30%1нат» как 48029 нельзя.
+        if let Some((начало_суффикса, суффикс)) = (начало_цифр + 1..self.позиция).find_map(|позиция| self.суффикс_числа(позиция).map(|суффикс| (позиция, суффикс))) {
+            let число: String = self.символы[начало_цифр..начало_суффикса].iter().collect();
+            self.отрезать_символы(начало_суффикса + суффикс.chars().count() - self.позиция);
+            диагностика!(&лок, "ОШИБКА", "Суффикс «{суффикс}» сливается с цифрами по основанию {основание}. Отделите его подчёркиванием: «{текст_основания}%{число}_{суффикс}»");
+            return Err(());
+        }
+        let текст: String = self.символы[начало..self.позиция].iter().collect();
+        if значение_целого(&текст).is_none() {
+            диагностика!(&лок, "ОШИБКА", "Слишком большое целое. Наибольшее допустимое значение {}", u64::MAX);
+            return Err(());
+        }
+        Ok(Лексема {
+            вид: ВидЛексемы::ЦелЧислоПоОснованию,
+            текст,
+            лок,
+        })
+    }
+
+    /// Пропускает остаток некорректного символьного литерала до закрывающей
+    /// кавычки или конца строки.
+    fn пропустить_символьный_литерал(&mut self) {
+        while let Some(сим) = self.текущий_символ().cloned() {
+            if сим == '\n' {
+                break;
+            }
+            self.отрезать_символ();
+            if сим == '\'' {
+                break;
+            }
+        }
+    }
+
     fn лок(&self) -> Лок {
         Лок {
             строка: self.строка + 1,
@@ -332,46 +517,45 @@ impl<'a> Лексер<'a> {
             })
         }
 
-        if self.имеет_приставку(&['1', '6', '%']) {
-            self.отрезать_символы(3);
+        if сим.is_ascii_digit() {
             let начало = self.позиция;
-            while self.символы.get(self.позиция).and_then(шестнадцатеричная_цифра).is_some() {
-                self.отрезать_символ();
-            }
-            let текст = self.символы[начало..self.позиция].iter().collect();
-            return Ok(Лексема {
-                вид: ВидЛексемы::ЦелШестЧисло,
-                текст,
-                лок,
-            });
-        }
-
-        if сим.is_numeric() {
-            let начало = self.позиция;
-            while self.символы.get(self.позиция).map(|сим| сим.is_numeric()).unwrap_or(false) {
-                self.отрезать_символ();
+            self.отрезать_цифры(10);
+            if Some('%') == self.символы.get(self.позиция).cloned() {
+                return self.целое_по_основанию(начало, лок);
             }
+            let mut вещественное = false;
             // Это условие крайне важно, чтобы разобрать выражение
             // "1..10" как "1", "..", "10".
             if Some('.') == self.символы.get(self.позиция).cloned() && Some('.') != self.символы.get(self.позиция + 1).cloned() {
                 self.отрезать_символ();
-                while self.символы.get(self.позиция).map(|сим| сим.is_numeric()).unwrap_or(false) {
+                self.отрезать_цифры(10);
+                вещественное = true;
+            }
+            if self.имеет_порядок() {
+                self.отрезать_символ();
+                if let Some('+' | '-') = self.текущий_символ() {
                     self.отрезать_символ();
                 }
-                let текст = self.символы[начало..self.позиция].iter().collect();
+                self.отрезать_цифры(10);
+                вещественное = true;
+            }
+            let текст: String = self.символы[начало..self.позиция].iter().collect();
+            if вещественное {
                 return Ok(Лексема {
                     вид: ВидЛексемы::ВещЧисло,
                     текст,
                     лок,
                 })
-            } else {
-                let текст = self.символы[начало..self.позиция].iter().collect();
-                return Ok(Лексема {
-                    вид: ВидЛексемы::ЦелЧисло,
-                    текст,
-                    лок,
-                })
             }
+            if значение_целого(&текст).is_none() {
+                диагностика!(&лок, "ОШИБКА", "Слишком большое целое. Наибольшее допустимое значение {}", u64::MAX);
+                return Err(());
+            }
+            return Ok(Лексема {
+                вид: ВидЛексемы::ЦелЧисло,
+                текст,
+                лок,
+            })
         }
 
         if сим == '«' {
@@ -526,6 +710,53 @@ impl<'a> Лексер<'a> {
             }
         }
 
+        if сим == '\'' {
+            self.отрезать_символ();
+            let знак = match self.текущий_символ().cloned() {
+                Some('\\') => {
+                    self.отрезать_символ();
+                    match self.текущий_символ().cloned() {
+                        Some('н') => '\n',
+                        Some('т') => '\t',
+                        Some('0') => '\0',
+                        Some('\\') => '\\',
+                        Some('\'') => '\'',
+                        Some(сим) => {
+                            диагностика!(&self.лок(), "ОШИБКА", "неизвестная поседовательность экранирования начинается с «{сим}»");
+                            self.пропустить_символьный_литерал();
+                            return Err(());
+                        }
+                        None => {
+                            диагностика!(&лок, "ОШИБКА", "незавершённый символьный литерал");
+                            return Err(());
+                        }
+                    }
+                }
+                Some('\'') => {
+                    self.отрезать_символ();
+                    диагностика!(&лок, "ОШИБКА", "Пустой символьный литерал");
+                    return Err(());
+                }
+                Some(сим) if сим != '\n' => сим,
+                _ => {
+                    диагностика!(&лок, "ОШИБКА", "незавершённый символьный литерал");
+                    return Err(());
+                }
+            };
+            self.отрезать_символ();
+            if self.текущий_символ().cloned() != Some('\'') {
+                self.пропустить_символьный_литерал();
+                диагностика!(&лок, "ОШИБКА", "Символьный литерал должен содержать ровно один символ");
+                return Err(());
+            }
+            self.отрезать_символ();
+            return Ok(Лексема {
+                вид: ВидЛексемы::Символ,
+                текст: знак.to_string(),
+                лок,
+            });
+        }
+
         for &(приставка, вид) in ПРИСТАВКИ_ПРЕПИНАНИЙ.iter() {
             if self.имеет_приставку(приставка) {
                 self.отрезать_символы(приставка.len());
@@ -540,20 +771,37 @@ impl<'a> Лексер<'a> {
     }
 
     pub fn вытащить_лексему(&mut self) -> Результат<Лексема> {
-        if let Some(лексема) = self.буфер.take() {
-            лексема
+        if self.буфер.len() > 0 {
+            self.буфер.remove(0)
         } else {
             self.следующая_лексема()
         }
     }
 
     pub fn подсмотреть_лексему(&mut self) -> Результат<Лексема> {
-        if self.буфер.is_none() {
-            self.буфер = Some(self.следующая_лексема())
+        if self.буфер.is_empty() {
+            let лексема = self.следующая_лексема();
+            self.буфер.push(лексема);
+        }
+        self.буфер[0].clone()
+    }
+
+    /// Подсмотреть лексему, которая идёт сразу после следующей.
+    ///
+    /// Нужно в тех редких местах грамматики, где одной лексемы
+    /// наперёд недостаточно. Например, чтобы отличить метку цикла
+    /// «внешний: пока ...» от обычного выражения.
+    pub fn подсмотреть_вторую_лексему(&mut self) -> Результат<Лексема> {
+        while self.буфер.len() < 2 {
+            let лексема = self.следующая_лексема();
+            self.буфер.push(лексема);
         }
-        self.буфер.clone().unwrap()
+        self.буфер[1].clone()
     }
 
+    /// Вытащить лексему одного из ожидаемых видов. Неожиданная лексема
+    /// остаётся во входном потоке, чтобы восстановление после ошибки могло
+    /// на неё посмотреть. Например, если на месте выражения повстречался «кц».
     pub fn вытащить_лексему_вида(&mut self, ожидаемые_виды: &[ВидЛексемы]) -> Результат<Лексема> {
         assert!(ожидаемые_виды.len() > 0);
 
@@ -588,6 +836,7 @@ impl<'a> Лексер<'a> {
                          действительный_вид = лексема.вид.сущ().текст);
         }
 
+        self.буфер.insert(0, Ok(лексема));
         Err(())
     }
 }
@@ -1,4 +1,3 @@
-use std::num::{IntErrorKind};
 use лексика::*;
 use диагностика::*;
 use super::Результат;
@@ -6,16 +5,35 @@ use super::Результат;
 #[derive(Clone)]
 pub struct Переменная {
     pub имя: Лексема,
-    pub тип: Выражение,
+    /// Если тип не указан, то переменная получает тип начального значения.
+    pub тип: Option<Выражение>,
+    pub значение: Option<Выражение>,
 }
 
 impl Переменная {
     pub fn разобрать(лекс: &mut Лексер) -> Результат<Переменная> {
         let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
-        let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Двоеточие])?;
-        let тип = Выражение::разобрать(лекс)?;
-        let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
-        Ok(Переменная{имя, тип})
+        let mut тип = None;
+        let mut лексема = лекс.вытащить_лексему_вида(&[ВидЛексемы::Двоеточие, ВидЛексемы::Присваивание, ВидЛексемы::ТочкаЗапятая])?;
+        if лексема.вид == ВидЛексемы::Двоеточие {
+            тип = Some(Выражение::разобрать(лекс)?);
+            лексема = лекс.вытащить_лексему_вида(&[ВидЛексемы::Присваивание, ВидЛексемы::ТочкаЗапятая])?;
+        }
+        let значение = match лексема.вид {
+            ВидЛексемы::ТочкаЗапятая => None,
+            ВидЛексемы::Присваивание => {
+                let значение = Выражение::разобрать(лекс)?;
+                let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
+                Some(значение)
+            }
+            _ => unreachable!()
+        };
+        if тип.is_none() && значение.is_none() {
+            диагностика!(&имя.лок, "ОШИБКА", "У переменной «{имя}» не указан ни тип, ни начальное значение", имя = имя.текст);
+            диагностика!(&имя.лок, "ИНФО", "Например: «пер {имя}: цел;» или «пер {имя} := 0;»", имя = имя.текст);
+            return Err(());
+        }
+        Ok(Переменная{имя, тип, значение})
     }
 }
 
@@ -94,11 +112,28 @@ pub struct Аргументы {
 pub enum Выражение {
     ЦелЧисло(Лексема, i64),
     НатЧисло(Лексема, u64),
-    ВещЧисло(Лексема, f32),
+    /// Вещественный литерал без суффикса. По умолчанию имеет тип «вещ32»,
+    /// но там, где ожидается «вещ64», принимает его, поэтому значение
+    /// хранится с двойной точностью.
+    ВещЧисло(Лексема, f64),
+    Вещ64Число(Лексема, f64),
+    /// Символьный литерал. Символ из ASCII имеет тип «нат8», остальные
+    /// имеют тип «символ».
+    Символ(Лексема, char),
     Лог(Лексема, bool),
     Строка(Лексема),
     Идент(Лексема),
     ЦепочкаВызовов{имя: Лексема, цепочка_аргументов: Vec<Аргументы>},
+    /// «строка»(индекс)(индекс)...
+    ЦепочкаСтроки{строка: Лексема, цепочка_аргументов: Vec<Аргументы>},
+    /// «от..до» в скобках после массива или среза. Берёт срез элементов с
+    /// «от» по «до» включительно, как в «для». Любая из границ может
+    /// отсутствовать, тогда срез идёт от начала или до конца.
+    Диапазон {
+        ключ: Лексема,
+        от: Option<Box<Выражение>>,
+        до: Option<Box<Выражение>>,
+    },
     УнарныйМинус {
         ключ: Лексема,
         выражение: Box<Выражение>,
@@ -112,7 +147,18 @@ pub enum Выражение {
     Отрицание {
         ключ: Лексема,
         выражение: Box<Выражение>,
-    }
+    },
+    /// «@выражение»
+    Адрес {
+        ключ: Лексема,
+        выражение: Box<Выражение>,
+    },
+    /// «выражение^»
+    Разыменование {
+        ключ: Лексема,
+        выражение: Box<Выражение>,
+    },
+    Пусто(Лексема),
 }
 
 impl Выражение {
@@ -121,77 +167,83 @@ impl Выражение {
             Выражение::ЦелЧисло(лексема, _) |
             Выражение::НатЧисло(лексема, _) |
             Выражение::ВещЧисло(лексема, _) |
+            Выражение::Вещ64Число(лексема, _) |
+            Выражение::Символ(лексема, _) |
             Выражение::Лог(лексема, _) |
             Выражение::Строка(лексема) |
+            Выражение::Пусто(лексема) |
             Выражение::Идент(лексема) => &лексема.лок,
             Выражение::Биноп{ключ, ..} => &ключ.лок,
             Выражение::ЦепочкаВызовов{имя, ..} => &имя.лок,
+            Выражение::ЦепочкаСтроки{строка, ..} => &строка.лок,
+            Выражение::Диапазон{ключ, ..} => &ключ.лок,
             Выражение::Отрицание{ключ, ..} => &ключ.лок,
             Выражение::УнарныйМинус{ключ, ..} => &ключ.лок,
+            Выражение::Адрес{ключ, ..} => &ключ.лок,
+            Выражение::Разыменование{ключ, ..} => &ключ.лок,
         }
     }
 
     fn разобрать_первичное(лекс: &mut Лексер) -> Результат<Выражение> {
+        let mut выражение = Выражение::разобрать_первичное_без_разыменования(лекс)?;
+        while лекс.подсмотреть_лексему()?.вид == ВидЛексемы::Крышка {
+            let ключ = лекс.вытащить_лексему().unwrap();
+            выражение = Выражение::Разыменование{ключ, выражение: Box::new(выражение)};
+        }
+        Ok(выражение)
+    }
+
+    fn разобрать_первичное_без_разыменования(лекс: &mut Лексер) -> Результат<Выражение> {
         let лексема = лекс.вытащить_лексему_вида(&[
             ВидЛексемы::ЦелЧисло,
-            ВидЛексемы::ЦелШестЧисло,
+            ВидЛексемы::ЦелЧислоПоОснованию,
             ВидЛексемы::ВещЧисло,
+            ВидЛексемы::Символ,
             ВидЛексемы::Идент,
             ВидЛексемы::Строка,
             ВидЛексемы::ОткрытаяСкобка,
             ВидЛексемы::Не,
             ВидЛексемы::КлючИстина,
             ВидЛексемы::КлючЛожь,
+            ВидЛексемы::КлючПусто,
             ВидЛексемы::Минус,
+            ВидЛексемы::Собака,
         ])?;
         match лексема.вид {
-            ВидЛексемы::ЦелШестЧисло => {
-                if лексема.текст.len() == 0 {
-                    диагностика!(&лексема.лок, "ОШИБКА", "Нет цифр для шестнадцатеричного литерала");
-                    return Err(());
-                }
-
-                let mut число: i64 = 0;
-                for цифра in лексема.текст.chars().map(|знак| шестнадцатеричная_цифра(&знак).expect("Все шестнадцатеричные цифры должны быть проверены на этапе лексического анализа")) {
-                    число = число*16 + цифра;
-                }
+            ВидЛексемы::ЦелЧисло | ВидЛексемы::ЦелЧислоПоОснованию => {
+                let число = значение_целого(&лексема.текст).expect("Переполнение целых должно быть проверено на этапе лексического анализа");
                 if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::Идент {
                     if лекс.подсмотреть_лексему()?.текст == "нат" {
                         let _ = лекс.вытащить_лексему()?;
-                        return Ok(Выражение::НатЧисло(лексема, число as u64));
+                        return Ok(Выражение::НатЧисло(лексема, число));
                     }
                 }
                 Ok(Выражение::ЦелЧисло(лексема, число as i64))
             }
-            ВидЛексемы::ЦелЧисло => {
-                let число: u64 = match лексема.текст.parse() {
-                    Ok(число) => число,
-                    Err(ошибка) => match ошибка.kind() {
-                        IntErrorKind::PosOverflow => {
-                            диагностика!(&лексема.лок, "ОШИБКА", "Слишком большое целое");
-                            return Err(());
-                        }
-                        IntErrorKind::Empty => unreachable!(),
-                        IntErrorKind::InvalidDigit => unreachable!(),
-                        IntErrorKind::NegOverflow => unreachable!(),
-                        IntErrorKind::Zero => unreachable!(),
-                        _ => {
-                            диагностика!(&лексема.лок, "ОШИБКА", "Некорректное целое число");
-                            return Err(());
-                        }
-                    }
-                };
+            ВидЛексемы::ВещЧисло => {
+                let текст = текст_вещественного(&лексема.текст);
                 if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::Идент {
-                    if лекс.подсмотреть_лексему()?.текст == "нат" {
+                    if лекс.подсмотреть_лексему()?.текст == "вещ64" {
                         let _ = лекс.вытащить_лексему()?;
-                        return Ok(Выражение::НатЧисло(лексема, число));
+                        return match текст.parse::<f64>() {
+                            Ok(число) if число.is_finite() => Ok(Выражение::Вещ64Число(лексема, число)),
+                            Ok(_) => {
+                                диагностика!(&лексема.лок, "ОШИБКА", "Вещественное число не помещается в тип «вещ64»");
+                                Err(())
+                            }
+                            Err(_ошибка) => {
+                                диагностика!(&лексема.лок, "ОШИБКА", "Некорректное вещественное число");
+                                Err(())
+                            }
+                        };
                     }
                 }
-                Ok(Выражение::ЦелЧисло(лексема, число as i64))
-            }
-            ВидЛексемы::ВещЧисло => {
-                match лексема.текст.parse() {
-                    Ok(число) => Ok(Выражение::ВещЧисло(лексема, число)),
+                match текст.parse::<f64>() {
+                    Ok(число) if (число as f32).is_finite() => Ok(Выражение::ВещЧисло(лексема, число)),
+                    Ok(_) => {
+                        диагностика!(&лексема.лок, "ОШИБКА", "Вещественное число не помещается в тип «вещ32»");
+                        Err(())
+                    }
                     Err(_ошибка) => {
                         диагностика!(&лексема.лок, "ОШИБКА", "Некорректное вещественное число");
                         Err(())
@@ -216,7 +268,28 @@ impl Выражение {
                     })
                 }
             },
-            ВидЛексемы::Строка => Ok(Выражение::Строка(лексема)),
+            ВидЛексемы::Символ => {
+                let знак = лексема.текст.chars().next().expect("Символьный литерал содержит ровно один символ");
+                Ok(Выражение::Символ(лексема, знак))
+            }
+            ВидЛексемы::Строка => {
+                let mut цепочка_аргументов = Vec::new();
+                while лекс.подсмотреть_лексему()?.вид == ВидЛексемы::ОткрытаяСкобка {
+                    let ключ = лекс.вытащить_лексему().unwrap();
+                    цепочка_аргументов.push(Аргументы {
+                        ключ,
+                        выражения: разобрать_список_аргументов_вызова(лекс)?
+                    });
+                }
+                if цепочка_аргументов.len() == 0 {
+                    Ok(Выражение::Строка(лексема))
+                } else {
+                    Ok(Выражение::ЦепочкаСтроки {
+                        строка: лексема,
+                        цепочка_аргументов,
+                    })
+                }
+            }
             ВидЛексемы::ОткрытаяСкобка => {
                 let выражение = Выражение::разобрать(лекс)?;
                 let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ЗакрытаяСкобка])?;
@@ -234,6 +307,13 @@ impl Выражение {
             }
             ВидЛексемы::КлючИстина => Ok(Выражение::Лог(лексема, true)),
             ВидЛексемы::КлючЛожь => Ok(Выражение::Лог(лексема, false)),
+            ВидЛексемы::КлючПусто => Ok(Выражение::Пусто(лексема)),
+            ВидЛексемы::Собака => {
+                let ключ = лексема;
+                // Разбираем цепочку полей целиком, чтобы «@а.б» означало «@(а.б)».
+                let выражение = Box::new(Выражение::разобрать_биноп(лекс, ВидБинопа::МАКС_ПРИОРИТЕТ)?);
+                Ok(Выражение::Адрес{ключ, выражение})
+            }
             _ => unreachable!(),
         }
     }
@@ -265,10 +345,28 @@ impl Выражение {
     }
 }
 
+/// Что проверяется в «когда»: одно значение или диапазон «от..до»
+/// включительно.
+#[derive(Debug)]
+pub enum ОбразецВилки {
+    Значение(Выражение),
+    Диапазон{от: Выражение, ключ: Лексема, до: Выражение},
+}
+
+impl ОбразецВилки {
+    pub fn лок(&self) -> &Лок {
+        match self {
+            ОбразецВилки::Значение(выражение) => выражение.лок(),
+            ОбразецВилки::Диапазон{от, ..} => от.лок(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ВеткаВилки {
     pub ключ: Лексема,
-    pub выражение: Выражение,
+    /// Ветка выбирается, если подходит хотя бы один из образцов.
+    pub образцы: Vec<ОбразецВилки>,
     pub тело: Vec<Утверждение>,
 }
 
@@ -276,24 +374,32 @@ pub struct ВеткаВилки {
 pub enum Утверждение {
     Присваивание{ключ: Лексема, левое: Выражение, правое: Выражение},
     Выражение{выражение: Выражение},
-    Пока{ключ: Лексема, условие: Выражение, тело: Vec<Утверждение>},
-    Для{ключ: Лексема, индекс: Лексема, нижняя_граница: Выражение, верхняя_граница: Выражение, тело: Vec<Утверждение>},
+    Пока{ключ: Лексема, метка: Option<Лексема>, условие: Выражение, тело: Vec<Утверждение>},
+    Для{ключ: Лексема, метка: Option<Лексема>, индекс: Лексема, нижняя_граница: Выражение, верхняя_граница: Выражение, тело: Vec<Утверждение>},
+    /// «для элемент в коллекция» либо «для индекс, элемент в коллекция».
+    /// Элемент является ссылкой на элемент массива или среза.
+    ДляКаждого{ключ: Лексема, метка: Option<Лексема>, индекс: Option<Лексема>, элемент: Лексема, коллекция: Выражение, тело: Vec<Утверждение>},
     Если{ключ: Лексема, условие: Выражение, тело: Vec<Утверждение>, иначе: Vec<Утверждение>},
     Вернуть{ключ: Лексема, выражение: Option<Выражение>},
-    ДекларацияПеременной{ключ: Лексема, имя: Лексема, тип: Выражение, значение: Option<Выражение>},
+    ДекларацияПеременной{ключ: Лексема, имя: Лексема, тип: Option<Выражение>, значение: Option<Выражение>},
     ДекларацияКонстанты{ключ: Лексема, имя: Лексема, значение: Выражение},
     Вилка {ключ: Лексема, выражение: Выражение, ветки: Vec<ВеткаВилки>, любое: Option<(Лексема, Vec<Утверждение>)>},
+    Прервать{ключ: Лексема, метка: Option<Лексема>},
+    Продолжить{ключ: Лексема, метка: Option<Лексема>},
 }
 
 #[derive(Debug)]
 pub struct Параметр {
     pub имя: Лексема,
     pub тип: Выражение,
+    /// Параметр объявлен как «пер имя: тип» и передается по ссылке.
+    pub ссылка: bool,
 }
 
 #[derive(Debug)]
 pub enum ТелоПроцедуры {
-    Внутренее { блок: Vec<Утверждение> },
+    /// Конец — это «кц» блока, либо «то», если тело состоит из одного утверждения.
+    Внутренее { блок: Vec<Утверждение>, конец: Лексема },
     Внешнее { символ: Лексема },
 }
 
@@ -305,8 +411,76 @@ pub struct Процедура {
     pub тело: ТелоПроцедуры,
 }
 
+fn разобрать_цикл(лекс: &mut Лексер, метка: Option<Лексема>) -> Результат<Утверждение> {
+    let ключ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючПока, ВидЛексемы::КлючДля])?;
+    match ключ.вид {
+        ВидЛексемы::КлючПока => {
+            let условие = Выражение::разобрать(лекс)?;
+            let тело = разобрать_блок_кода(лекс)?;
+            Ok(Утверждение::Пока{ключ, метка, условие, тело})
+        }
+        ВидЛексемы::КлючДля => {
+            let индекс = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+            let лексема = лекс.подсмотреть_лексему()?;
+            match лексема.вид {
+                ВидЛексемы::Присваивание => {}
+                ВидЛексемы::Запятая => {
+                    let _ = лекс.вытащить_лексему().unwrap();
+                    let элемент = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+                    return разобрать_обход_коллекции(лекс, ключ, метка, Some(индекс), элемент);
+                }
+                ВидЛексемы::Идент if лексема.текст == "в" => {
+                    return разобрать_обход_коллекции(лекс, ключ, метка, None, индекс);
+                }
+                _ => {
+                    диагностика!(&лексема.лок, "ОШИБКА", "Ожидалось «:=» либо «в» после переменной цикла «для», но повстречалось «{текст}»", текст = лексема.текст);
+                    return Err(());
+                }
+            }
+            let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Присваивание])?;
+            let нижняя_граница = Выражение::разобрать(лекс)?;
+            let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаТочка])?;
+            let верхняя_граница = Выражение::разобрать(лекс)?;
+            let тело = разобрать_блок_кода(лекс)?;
+            Ok(Утверждение::Для{ключ, метка, индекс, нижняя_граница, верхняя_граница, тело})
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn разобрать_обход_коллекции(лекс: &mut Лексер, ключ: Лексема, метка: Option<Лексема>, индекс: Option<Лексема>, элемент: Лексема) -> Результат<Утверждение> {
+    let в = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
+    if в.текст != "в" {
+        диагностика!(&в.лок, "ОШИБКА", "Ожидалось «в» после переменных цикла «для», но повстречалось «{текст}»", текст = в.текст);
+        return Err(());
+    }
+    let коллекция = Выражение::разобрать(лекс)?;
+    let тело = разобрать_блок_кода(лекс)?;
+    Ok(Утверждение::ДляКаждого{ключ, метка, индекс, элемент, коллекция, тело})
+}
+
+fn разобрать_выход_из_цикла(лекс: &mut Лексер) -> Результат<Утверждение> {
+    let ключ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючПрервать, ВидЛексемы::КлючПродолжить])?;
+    let метка = if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::Идент {
+        Some(лекс.вытащить_лексему().unwrap())
+    } else {
+        None
+    };
+    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
+    match ключ.вид {
+        ВидЛексемы::КлючПрервать => Ok(Утверждение::Прервать{ключ, метка}),
+        ВидЛексемы::КлючПродолжить => Ok(Утверждение::Продолжить{ключ, метка}),
+        _ => unreachable!(),
+    }
+}
+
 fn разобрать_утверждение(лекс: &mut Лексер) -> Результат<Утверждение> {
     match лекс.подсмотреть_лексему()?.вид {
+        ВидЛексемы::Идент if лекс.подсмотреть_вторую_лексему()?.вид == ВидЛексемы::Двоеточие => {
+            let метка = лекс.вытащить_лексему().unwrap();
+            let _ = лекс.вытащить_лексему().unwrap();
+            разобрать_цикл(лекс, Some(метка))
+        }
         ВидЛексемы::КлючЕсли => {
             let ключ = лекс.вытащить_лексему().unwrap();
             let условие = Выражение::разобрать(лекс)?;
@@ -320,22 +494,8 @@ fn разобрать_утверждение(лекс: &mut Лексер) -> Р
             }
             Ok(Утверждение::Если{ключ, условие, тело, иначе})
         }
-        ВидЛексемы::КлючПока => {
-            let ключ = лекс.вытащить_лексему().unwrap();
-            let условие = Выражение::разобрать(лекс)?;
-            let тело = разобрать_блок_кода(лекс)?;
-            Ok(Утверждение::Пока{ключ, условие, тело})
-        }
-        ВидЛексемы::КлючДля => {
-            let ключ = лекс.вытащить_лексему().unwrap();
-            let индекс = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
-            let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Присваивание])?;
-            let нижняя_граница = Выражение::разобрать(лекс)?;
-            let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаТочка])?;
-            let верхняя_граница = Выражение::разобрать(лекс)?;
-            let тело = разобрать_блок_кода(лекс)?;
-            Ok(Утверждение::Для{ключ, индекс, нижняя_граница, верхняя_граница, тело})
-        }
+        ВидЛексемы::КлючПока | ВидЛексемы::КлючДля => разобрать_цикл(лекс, None),
+        ВидЛексемы::КлючПрервать | ВидЛексемы::КлючПродолжить => разобрать_выход_из_цикла(лекс),
         ВидЛексемы::КлючВернуть => {
             let ключ = лекс.вытащить_лексему().unwrap();
             if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::ТочкаЗапятая {
@@ -349,18 +509,7 @@ fn разобрать_утверждение(лекс: &mut Лексер) -> Р
         }
         ВидЛексемы::КлючПер => {
             let ключ = лекс.вытащить_лексему().unwrap();
-            let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
-            let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Двоеточие])?;
-            let тип = Выражение::разобрать(лекс)?;
-            let значение = match лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая, ВидЛексемы::Присваивание])?.вид {
-                ВидЛексемы::ТочкаЗапятая => None,
-                ВидЛексемы::Присваивание => {
-                    let значение = Выражение::разобрать(лекс)?;
-                    let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
-                    Some(значение)
-                }
-                _ => unreachable!()
-            };
+            let Переменная{имя, тип, значение} = Переменная::разобрать(лекс)?;
             Ok(Утверждение::ДекларацияПеременной{ключ, имя, тип, значение})
         }
         ВидЛексемы::КлючКонст => {
@@ -390,9 +539,24 @@ fn разобрать_утверждение(лекс: &mut Лексер) -> Р
                             лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючКц])?;
                             break 'разбор_веток;
                         } else {
+                            let mut образцы = Vec::new();
+                            loop {
+                                let от = Выражение::разобрать(лекс)?;
+                                if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::ТочкаТочка {
+                                    let ключ = лекс.вытащить_лексему().unwrap();
+                                    let до = Выражение::разобрать(лекс)?;
+                                    образцы.push(ОбразецВилки::Диапазон{от, ключ, до});
+                                } else {
+                                    образцы.push(ОбразецВилки::Значение(от));
+                                }
+                                if лекс.подсмотреть_лексему()?.вид != ВидЛексемы::Запятая {
+                                    break;
+                                }
+                                let _ = лекс.вытащить_лексему().unwrap();
+                            }
                             ветки.push(ВеткаВилки {
                                 ключ: ключ_ветки,
-                                выражение: Выражение::разобрать(лекс)?,
+                                образцы,
                                 тело: разобрать_блок_кода(лекс)?,
                             });
                         }
@@ -422,21 +586,85 @@ fn разобрать_утверждение(лекс: &mut Лексер) -> Р
     }
 }
 
+/// Пропустить лексемы до конца утверждения, которое не удалось разобрать.
+/// Останавливается после «;» или после закрывающего «кц» вложенного блока
+/// (если за ним не следует «иначе»), а также перед «кц» самого блока,
+/// в котором находится утверждение.
+fn пропустить_утверждение(лекс: &mut Лексер) {
+    let mut вложенность = 0;
+    loop {
+        let лексема = match лекс.подсмотреть_лексему() {
+            Ok(лексема) => лексема,
+            Err(()) => {
+                let _ = лекс.вытащить_лексему();
+                continue;
+            }
+        };
+        match лексема.вид {
+            ВидЛексемы::Конец => return,
+            ВидЛексемы::КлючКц if вложенность == 0 => return,
+            _ => {}
+        }
+        let _ = лекс.вытащить_лексему();
+        match лексема.вид {
+            ВидЛексемы::ТочкаЗапятая if вложенность == 0 => return,
+            ВидЛексемы::КлючНч => вложенность += 1,
+            ВидЛексемы::КлючКц => {
+                вложенность -= 1;
+                let иначе = matches!(лекс.подсмотреть_лексему(), Ok(Лексема{вид: ВидЛексемы::КлючИначе, ..}));
+                if вложенность == 0 && !иначе {
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Утверждения, которые не удалось разобрать, пропускаются, чтобы
+/// сообщить сразу обо всех синтаксических ошибках блока. О том, что
+/// ошибки были, вызывающий узнаёт по количеству_ошибок().
 fn разобрать_блок_кода(лекс: &mut Лексер) -> Результат<Vec<Утверждение>> {
+    разобрать_блок_кода_с_концом(лекс).map(|(блок, _)| блок)
+}
+
+fn разобрать_блок_кода_с_концом(лекс: &mut Лексер) -> Результат<(Vec<Утверждение>, Лексема)> {
     let mut блок = Vec::new();
     let ключ = лекс.вытащить_лексему_вида(&[ВидЛексемы::КлючНч, ВидЛексемы::КлючТо])?;
+    let mut конец = ключ.clone();
     match ключ.вид {
         ВидЛексемы::КлючНч => loop {
-            if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::КлючКц {
-                let _ = лекс.вытащить_лексему()?;
-                break;
+            if let Ok(лексема) = лекс.подсмотреть_лексему() {
+                if лексема.вид == ВидЛексемы::КлючКц {
+                    конец = лекс.вытащить_лексему()?;
+                    break;
+                }
+            }
+            match разобрать_утверждение(лекс) {
+                Ok(утверждение) => блок.push(утверждение),
+                Err(()) => {
+                    if слишком_много_ошибок() {
+                        return Err(());
+                    }
+                    пропустить_утверждение(лекс);
+                    if matches!(лекс.подсмотреть_лексему(), Ok(Лексема{вид: ВидЛексемы::Конец, ..})) {
+                        return Err(());
+                    }
+                }
+            }
+        }
+        ВидЛексемы::КлючТо => match разобрать_утверждение(лекс) {
+            Ok(утверждение) => блок.push(утверждение),
+            Err(()) => {
+                if слишком_много_ошибок() {
+                    return Err(());
+                }
+                пропустить_утверждение(лекс);
             }
-            блок.push(разобрать_утверждение(лекс)?);
         }
-        ВидЛексемы::КлючТо => блок.push(разобрать_утверждение(лекс)?),
         _ => unreachable!()
     }
-    Ok(блок)
+    Ok((блок, конец))
 }
 
 fn разобрать_список_аргументов_вызова(лекс: &mut Лексер) -> Результат<Vec<Выражение>> {
@@ -447,7 +675,7 @@ fn разобрать_список_аргументов_вызова(лекс: &
         let _ = лекс.вытащить_лексему()?;
     } else {
         'разбор_аргументов: loop {
-            аргументы.push(Выражение::разобрать(лекс)?);
+            аргументы.push(разобрать_аргумент_вызова(лекс)?);
             let лексема = лекс.вытащить_лексему_вида(&[
                 ВидЛексемы::ЗакрытаяСкобка,
                 ВидЛексемы::Запятая
@@ -460,6 +688,25 @@ fn разобрать_список_аргументов_вызова(лекс: &
     Ok(аргументы)
 }
 
+/// Аргумент вызова, либо диапазон «от..до» с необязательными границами.
+fn разобрать_аргумент_вызова(лекс: &mut Лексер) -> Результат<Выражение> {
+    let от = if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::ТочкаТочка {
+        None
+    } else {
+        let выражение = Выражение::разобрать(лекс)?;
+        if лекс.подсмотреть_лексему()?.вид != ВидЛексемы::ТочкаТочка {
+            return Ok(выражение);
+        }
+        Some(Box::new(выражение))
+    };
+    let ключ = лекс.вытащить_лексему().unwrap();
+    let до = match лекс.подсмотреть_лексему()?.вид {
+        ВидЛексемы::ЗакрытаяСкобка | ВидЛексемы::Запятая => None,
+        _ => Some(Box::new(Выражение::разобрать(лекс)?)),
+    };
+    Ok(Выражение::Диапазон{ключ, от, до})
+}
+
 fn разобрать_список_параметров_процедуры(лекс: &mut Лексер) -> Результат<Vec<Параметр>> {
     let mut параметры: Vec<Параметр> = Vec::new();
     let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ОткрытаяСкобка])?;
@@ -467,6 +714,12 @@ fn разобрать_список_параметров_процедуры(ле
         let _ = лекс.вытащить_лексему()?;
     } else {
         'разбор_параметров: loop {
+            let ссылка = if лекс.подсмотреть_лексему()?.вид == ВидЛексемы::КлючПер {
+                let _ = лекс.вытащить_лексему().unwrap();
+                true
+            } else {
+                false
+            };
             let имя = лекс.вытащить_лексему_вида(&[ВидЛексемы::Идент])?;
             if let Some(существующий_параметр) = параметры.iter().find(|параметр| параметр.имя.текст == имя.текст) {
                 диагностика!(&имя.лок, "ОШИБКА", "переопределение параметра «{имя}»",
@@ -476,7 +729,7 @@ fn разобрать_список_параметров_процедуры(ле
             }
             let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::Двоеточие])?;
             let тип = Выражение::разобрать(лекс)?;
-            параметры.push(Параметр {имя, тип});
+            параметры.push(Параметр {имя, тип, ссылка});
             let лексема = лекс.вытащить_лексему_вида(&[
                 ВидЛексемы::ЗакрытаяСкобка,
                 ВидЛексемы::Запятая
@@ -506,8 +759,8 @@ impl Процедура {
             let _ = лекс.вытащить_лексему_вида(&[ВидЛексемы::ТочкаЗапятая])?;
             ТелоПроцедуры::Внешнее {символ}
         } else {
-            let блок = разобрать_блок_кода(лекс)?;
-            ТелоПроцедуры::Внутренее {блок}
+            let (блок, конец) = разобрать_блок_кода_с_концом(лекс)?;
+            ТелоПроцедуры::Внутренее {блок, конец}
         };
         Ok(Процедура{имя, параметры, тело, тип_результата})
     }
@@ -17,16 +17,40 @@ pub struct Структура {
     pub поля: HashMap<String, Поле>,
 }
 
+#[derive(Clone)]
+pub struct Перечисление {
+    pub имя: Лексема,
+    pub значения: Vec<Лексема>,
+}
+
+impl Перечисление {
+    pub fn значение(&self, имя: &str) -> Option<usize> {
+        self.значения.iter().position(|значение| значение.текст == имя)
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Тип {
     Нат8,
+    Нат16,
+    Нат32,
     Нат64,
+    Цел8,
+    Цел16,
+    Цел32,
     Цел64,
     Вещ32,
+    Вещ64,
+    /// Кодовая точка Юникода.
+    Символ,
     Лог,
     Массив { размер: usize, тип_элемента: Box<Тип> },
     Срез { тип_элемента: Box<Тип> },
     Структура(String),
+    Перечисление(String),
+    Указатель { тип_элемента: Box<Тип> },
+    /// Тип литерала «пусто». Совместим с любым указателем.
+    Пусто,
 }
 
 pub const СРЕЗ_РАЗМЕР_СМЕЩЕНИЕ: usize = 0;
@@ -35,39 +59,72 @@ pub const СРЕЗ_АДРЕС_СМЕЩЕНИЕ: usize = 8;
 impl Тип {
     pub fn примитивный(&self) -> bool {
         match self {
-            Тип::Цел64 | Тип::Нат8 | Тип::Нат64 | Тип::Вещ32 | Тип::Лог => true,
+            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 |
+            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 |
+            Тип::Вещ32 | Тип::Вещ64 | Тип::Символ | Тип::Лог | Тип::Перечисление(_) | Тип::Указатель{..} | Тип::Пусто => true,
             Тип::Массив {..} | Тип::Срез {..} | Тип::Структура {..} => false,
         }
     }
 
     pub fn примитивное_знаковое_чтение(&self) -> Option<bool> {
         match self {
-            Тип::Цел64 => Some(true),
-            Тип::Нат8 | Тип::Нат64 | Тип::Вещ32 | Тип::Лог => Some(false),
+            Тип::Цел8 | Тип::Цел16 | Тип::Цел32 | Тип::Цел64 => Some(true),
+            Тип::Нат8 | Тип::Нат16 | Тип::Нат32 | Тип::Нат64 | Тип::Вещ32 | Тип::Вещ64 | Тип::Символ | Тип::Лог | Тип::Перечисление(_) | Тип::Указатель{..} | Тип::Пусто => Some(false),
+            Тип::Массив {..} | Тип::Срез {..} | Тип::Структура {..} => None,
+        }
+    }
+
+    /// Диапазон значений целочисленного типа.
+    pub fn диапазон_целого(&self) -> Option<(i128, i128)> {
+        match self {
+            Тип::Нат8 => Some((0, u8::MAX as i128)),
+            Тип::Нат16 => Some((0, u16::MAX as i128)),
+            Тип::Нат32 => Some((0, u32::MAX as i128)),
+            Тип::Нат64 => Some((0, u64::MAX as i128)),
+            Тип::Цел8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            Тип::Цел16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            Тип::Цел32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            Тип::Цел64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            Тип::Вещ32 | Тип::Вещ64 | Тип::Символ | Тип::Лог | Тип::Перечисление(_) | Тип::Указатель{..} | Тип::Пусто |
             Тип::Массив {..} | Тип::Срез {..} | Тип::Структура {..} => None,
         }
     }
 
     pub fn текст(&self) -> String {
         match self {
+            Тип::Цел8 => "цел8".to_string(),
+            Тип::Цел16 => "цел16".to_string(),
+            Тип::Цел32 => "цел32".to_string(),
             Тип::Цел64 => "цел64".to_string(),
             Тип::Нат8 => "нат8".to_string(),
+            Тип::Нат16 => "нат16".to_string(),
+            Тип::Нат32 => "нат32".to_string(),
             Тип::Нат64 => "нат64".to_string(),
             Тип::Вещ32 => "вещ32".to_string(),
+            Тип::Вещ64 => "вещ64".to_string(),
+            Тип::Символ => "символ".to_string(),
             Тип::Лог => "лог".to_string(),
             Тип::Массив {тип_элемента, размер} => format!("массив({размер}, {тип_элемента})", тип_элемента = тип_элемента.текст()),
             Тип::Срез {тип_элемента} => format!("срез({тип_элемента})", тип_элемента = тип_элемента.текст()),
-            Тип::Структура(имя) => имя.clone(),
+            Тип::Структура(имя) | Тип::Перечисление(имя) => имя.clone(),
+            Тип::Указатель {тип_элемента} => format!("указатель({тип_элемента})", тип_элемента = тип_элемента.текст()),
+            Тип::Пусто => "пусто".to_string(),
         }
     }
 
     pub fn размер(&self, структуры: &HashMap<String, Структура>) -> usize {
         match self {
-            Тип::Нат8 => 1,
+            Тип::Нат8 | Тип::Цел8 => 1,
+            Тип::Нат16 | Тип::Цел16 => 2,
+            Тип::Нат32 | Тип::Цел32 => 4,
             Тип::Нат64 => 8,
             Тип::Цел64 => 8,
             Тип::Вещ32 => 4,
+            Тип::Вещ64 => 8,
+            Тип::Символ => 4,
             Тип::Лог => 8,
+            Тип::Перечисление(_) => 8,
+            Тип::Указатель {..} | Тип::Пусто => 8,
             Тип::Массив {тип_элемента, размер} => тип_элемента.размер(структуры) * размер,
             Тип::Срез {..} => 16, // Два 64-х битных числа: указатель на начало и размер.
             Тип::Структура (имя) => {
@@ -83,6 +140,10 @@ impl Тип {
 pub fn проверить_типы(лок: &Лок, ожидаемый_тип: &Тип, действительный_тип: &Тип) -> Результат<()> {
     if ожидаемый_тип == действительный_тип {
         Ok(())
+    } else if let (Тип::Указатель{..}, Тип::Пусто) | (Тип::Пусто, Тип::Указатель{..}) = (ожидаемый_тип, действительный_тип) {
+        // Операнды сравнения проверяются в обе стороны, поэтому «пусто»
+        // совместимо с указателем и слева, и справа.
+        Ok(())
     } else {
         диагностика!(лок, "ОШИБКА", "Несоответствие типов данных. Ожидался тип «{ожидаемый}», но повстречался тип «{действительный}»",
                      ожидаемый = ожидаемый_тип.текст(),
@@ -2,17 +2,48 @@ use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::convert::TryInto;
 use компилятор::ПП;
 use компилятор::ВидИнструкции;
+use интерпретатор::РАЗМЕР_СЛОВА;
 use типизация::*;
+use диагностика::Лок;
 use Результат;
 
+/// Печатает в stderr сообщение об ошибке времени исполнения с локацией
+/// инструкции и завершает программу с кодом 1.
+fn завершить_с_ошибкой(файл: &mut impl Write, лок: &Лок, текст: &str) {
+    let сообщение = format!("{путь_к_файлу}:{строка}:{столбец}: ОШИБКА ВРЕМЕНИ ИСПОЛНЕНИЯ: {текст}\n",
+                            путь_к_файлу = лок.путь_к_файлу.display(),
+                            строка = лок.строка,
+                            столбец = лок.столбец);
+    let _ = writeln!(файл, "    mov rax, 1 ; SYS_write");
+    let _ = writeln!(файл, "    mov rdi, 2 ; stderr");
+    let _ = writeln!(файл, "    mov rsi, .сообщение");
+    let _ = writeln!(файл, "    mov rdx, {длина}", длина = сообщение.len());
+    let _ = writeln!(файл, "    syscall");
+    let _ = writeln!(файл, "    mov rax, 60 ; SYS_exit");
+    let _ = writeln!(файл, "    mov rdi, 1");
+    let _ = writeln!(файл, "    syscall");
+    let _ = write!(файл, ".сообщение: db");
+    for (индекс_байта, байт) in сообщение.bytes().enumerate() {
+        if индекс_байта > 0 {
+            let _ = write!(файл, ",");
+        }
+        let _ = write!(файл, " {байт:#04X}");
+    }
+    let _ = writeln!(файл);
+}
+
 fn сгенерировать_инструкции(файл: &mut impl Write, пп: &ПП, точка_входа_программы: usize) -> Результат<()> {
     let mut внешние_символы: Vec<_> = пп.внешние_символы.iter().collect();
     внешние_символы.sort_by_key(|(_, индекс)| *индекс);
     // https://stackoverflow.com/questions/18024672/what-registers-are-preserved-through-a-linux-x86-64-function-call
     let _ = writeln!(файл, "    mov r12, начало_второго_стека");
     let _ = writeln!(файл, "    mov r13, начало_второго_стека");
+    if let Some(точка_инициализации) = пп.точка_инициализации {
+        let _ = writeln!(файл, "    call инструкция_{точка_инициализации}");
+    }
     let _ = writeln!(файл, "    call инструкция_{точка_входа_программы}");
     let _ = writeln!(файл, "    mov rax, 60");
     let _ = writeln!(файл, "    mov rdi, 0");
@@ -33,6 +64,52 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    push rax");
                 let _ = writeln!(файл, "    push rax");
             }
+            ВидИнструкции::Обменять => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    push rax");
+                let _ = writeln!(файл, "    push rbx");
+            }
+            ВидИнструкции::Прокрутить => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rcx");
+                let _ = writeln!(файл, "    push rbx");
+                let _ = writeln!(файл, "    push rax");
+                let _ = writeln!(файл, "    push rcx");
+            }
+            &ВидИнструкции::ПроверитьДиапазон{включительно} => {
+                let _ = writeln!(файл, "    pop rcx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rbx");
+                if включительно {
+                    // Конец меньше количества, так что единица к нему не переполнится.
+                    let _ = writeln!(файл, "    cmp rcx, rbx");
+                    let _ = writeln!(файл, "    jae .ошибка");
+                    let _ = writeln!(файл, "    inc rcx");
+                    let _ = writeln!(файл, "    push rax");
+                    let _ = writeln!(файл, "    push rcx");
+                    let _ = writeln!(файл, "    cmp rax, rcx");
+                    let _ = writeln!(файл, "    jbe инструкция_{следующая}", следующая = индекс + 1);
+                } else {
+                    let _ = writeln!(файл, "    push rax");
+                    let _ = writeln!(файл, "    push rcx");
+                    let _ = writeln!(файл, "    cmp rax, rcx");
+                    let _ = writeln!(файл, "    ja .ошибка");
+                    let _ = writeln!(файл, "    cmp rcx, rbx");
+                    let _ = writeln!(файл, "    jbe инструкция_{следующая}", следующая = индекс + 1);
+                }
+                let _ = writeln!(файл, ".ошибка:");
+                завершить_с_ошибкой(файл, &инструкция.лок, "Диапазон выходит за границы");
+            }
+            ВидИнструкции::ПроверитьГраницы => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    push rax");
+                let _ = writeln!(файл, "    cmp rax, rbx");
+                let _ = writeln!(файл, "    jb инструкция_{следующая}", следующая = индекс + 1);
+                завершить_с_ошибкой(файл, &инструкция.лок, "Индекс выходит за границы");
+            }
             ВидИнструкции::Натуральное(значение) => {
                 let _ = writeln!(файл, "    mov rax, {значение}");
                 let _ = writeln!(файл, "    push rax");
@@ -91,8 +168,9 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    mov [rsi], al");
             }
             ВидИнструкции::Записать16 => {
-                сделать!(&инструкция.лок, "Кодогенерация инструкции Записать16");
-                return Err(())
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    mov [rsi], ax");
             }
             ВидИнструкции::Записать32 => {
                 let _ = writeln!(файл, "    pop rsi");
@@ -111,8 +189,10 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    push rax");
             }
             ВидИнструкции::ПрочитатьБезЗнак16 => {
-                сделать!(&инструкция.лок, "Кодогенерация инструкции ПрочитатьБезЗнак16");
-                return Err(())
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    xor rax, rax");
+                let _ = writeln!(файл, "    mov ax, [rsi]");
+                let _ = writeln!(файл, "    push rax");
             }
             ВидИнструкции::ПрочитатьБезЗнак32 => {
                 let _ = writeln!(файл, "    pop rsi");
@@ -121,16 +201,19 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    push rax");
             }
             ВидИнструкции::ПрочитатьЗнак8 => {
-                сделать!(&инструкция.лок, "Кодогенерация инструкции ПрочитатьЗнак8");
-                return Err(())
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    movsx rax, byte [rsi]");
+                let _ = writeln!(файл, "    push rax");
             }
             ВидИнструкции::ПрочитатьЗнак16 => {
-                сделать!(&инструкция.лок, "Кодогенерация инструкции ПрочитатьЗнак16");
-                return Err(())
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    movsx rax, word [rsi]");
+                let _ = writeln!(файл, "    push rax");
             }
             ВидИнструкции::ПрочитатьЗнак32 => {
-                сделать!(&инструкция.лок, "Кодогенерация инструкции ПрочитатьЗнак32");
-                return Err(())
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    movsxd rax, dword [rsi]");
+                let _ = writeln!(файл, "    push rax");
             }
             ВидИнструкции::Прочитать64 => {
                 let _ = writeln!(файл, "    pop rsi");
@@ -154,6 +237,36 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    setz al");
                 let _ = writeln!(файл, "    push rax");
             }
+            ВидИнструкции::СравнитьПамять => {
+                let _ = writeln!(файл, "    pop rdx");
+                let _ = writeln!(файл, "    pop rdi");
+                let _ = writeln!(файл, "    pop rcx");
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    xor rax, rax");
+                let _ = writeln!(файл, "    mov rbx, rcx");
+                let _ = writeln!(файл, "    sub rbx, rdx");
+                let _ = writeln!(файл, "    cmp rcx, rdx");
+                let _ = writeln!(файл, "    cmova rcx, rdx");
+                let _ = writeln!(файл, "    cld");
+                let _ = writeln!(файл, "    test rcx, rcx");
+                let _ = writeln!(файл, "    jz .длины");
+                let _ = writeln!(файл, "    repe cmpsb");
+                let _ = writeln!(файл, "    jne .байты");
+                // Общее начало совпало, сравниваем длины.
+                let _ = writeln!(файл, ".длины:");
+                let _ = writeln!(файл, "    test rbx, rbx");
+                let _ = writeln!(файл, "    jz .конец");
+                let _ = writeln!(файл, "    mov rax, 1");
+                let _ = writeln!(файл, "    jg .конец");
+                let _ = writeln!(файл, "    mov rax, -1");
+                let _ = writeln!(файл, "    jmp .конец");
+                let _ = writeln!(файл, ".байты:");
+                let _ = writeln!(файл, "    mov rax, 1");
+                let _ = writeln!(файл, "    ja .конец");
+                let _ = writeln!(файл, "    mov rax, -1");
+                let _ = writeln!(файл, ".конец:");
+                let _ = writeln!(файл, "    push rax");
+            }
             ВидИнструкции::НатСложение => {
                 let _ = writeln!(файл, "    pop rbx");
                 let _ = writeln!(файл, "    pop rax");
@@ -376,6 +489,16 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    movd eax, xmm0");
                 let _ = writeln!(файл, "    push rax");
             }
+            ВидИнструкции::Вещ32Равно => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movd xmm0, eax");
+                let _ = writeln!(файл, "    movd xmm1, ebx");
+                let _ = writeln!(файл, "    cmpeqss xmm0, xmm1");
+                let _ = writeln!(файл, "    movd eax, xmm0");
+                let _ = writeln!(файл, "    and rax, 1");
+                let _ = writeln!(файл, "    push rax");
+            }
             ВидИнструкции::Вещ32Больше => {
                 let _ = writeln!(файл, "    pop rbx");
                 let _ = writeln!(файл, "    pop rax");
@@ -403,6 +526,138 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    movd eax, xmm0");
                 let _ = writeln!(файл, "    push rax");
             }
+            ВидИнструкции::КонвертНат64Вещ64 => {
+                // СДЕЛАТЬ: как и для вещ32, большие натуральные конвертируются как знаковые.
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pxor xmm0, xmm0");
+                let _ = writeln!(файл, "    cvtsi2sd xmm0, rax");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::КонвертЦел64Вещ64 => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pxor xmm0, xmm0");
+                let _ = writeln!(файл, "    cvtsi2sd xmm0, rax");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::КонвертВещ64Нат64 => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    cvttsd2si rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::КонвертВещ64Цел64 => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    cvttsd2si rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::КонвертВещ32Вещ64 => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movd xmm0, eax");
+                let _ = writeln!(файл, "    cvtss2sd xmm0, xmm0");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::КонвертВещ64Вещ32 => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    cvtsd2ss xmm0, xmm0");
+                let _ = writeln!(файл, "    movd eax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Умножение => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    mulsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Деление => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    divsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Сложение => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    addsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Вычитание => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    subsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Меньше => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    cmpltsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    and rax, 1");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64МеньшеРавно => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    cmplesd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    and rax, 1");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Равно => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    cmpeqsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    and rax, 1");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Больше => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    cmpnlesd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    and rax, 1");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64БольшеРавно => {
+                let _ = writeln!(файл, "    pop rbx");
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    movq xmm0, rax");
+                let _ = writeln!(файл, "    movq xmm1, rbx");
+                let _ = writeln!(файл, "    cmpnltsd xmm0, xmm1");
+                let _ = writeln!(файл, "    movq rax, xmm0");
+                let _ = writeln!(файл, "    and rax, 1");
+                let _ = writeln!(файл, "    push rax");
+            }
+            ВидИнструкции::Вещ64Отриц => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    btc rax, 63");
+                let _ = writeln!(файл, "    push rax");
+            }
             ВидИнструкции::ЛогОтрицание => {
                 let _ = writeln!(файл, "    xor rbx, rbx");
                 let _ = writeln!(файл, "    pop rax");
@@ -457,21 +712,206 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                 let _ = writeln!(файл, "    syscall");
                 let _ = writeln!(файл, "    push rax");
             }
+            ВидИнструкции::ВыделитьПамять{размер_элемента} => {
+                // Каждый блок выделяется отдельным mmap. Перед блоком храним
+                // его полный размер, чтобы знать сколько передавать в munmap.
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    mov rbx, {размер_элемента}");
+                let _ = writeln!(файл, "    mul rbx");
+                let _ = writeln!(файл, "    jo .неудача");
+                let _ = writeln!(файл, "    add rax, 8");
+                let _ = writeln!(файл, "    jc .неудача");
+                let _ = writeln!(файл, "    mov rsi, rax");
+                let _ = writeln!(файл, "    push rsi");
+                let _ = writeln!(файл, "    mov rax, 9 ; SYS_mmap");
+                let _ = writeln!(файл, "    mov rdi, 0");
+                let _ = writeln!(файл, "    mov rdx, 3 ; PROT_READ | PROT_WRITE");
+                let _ = writeln!(файл, "    mov r10, 34 ; MAP_PRIVATE | MAP_ANONYMOUS");
+                let _ = writeln!(файл, "    mov r8, -1");
+                let _ = writeln!(файл, "    mov r9, 0");
+                let _ = writeln!(файл, "    syscall");
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    cmp rax, -4096");
+                let _ = writeln!(файл, "    ja .неудача");
+                let _ = writeln!(файл, "    mov [rax], rsi");
+                let _ = writeln!(файл, "    add rax, 8");
+                let _ = writeln!(файл, "    push rax");
+                let _ = writeln!(файл, "    jmp инструкция_{следующая}", следующая = индекс + 1);
+                let _ = writeln!(файл, ".неудача:");
+                завершить_с_ошибкой(файл, &инструкция.лок, "Не удалось выделить память");
+            }
+            ВидИнструкции::ОсвободитьПамять => {
+                let _ = writeln!(файл, "    pop rdi");
+                let _ = writeln!(файл, "    test rdi, rdi");
+                let _ = writeln!(файл, "    jz инструкция_{следующая}", следующая = индекс + 1);
+                let _ = writeln!(файл, "    sub rdi, 8");
+                let _ = writeln!(файл, "    mov rsi, [rdi]");
+                let _ = writeln!(файл, "    mov rax, 11 ; SYS_munmap");
+                let _ = writeln!(файл, "    syscall");
+            }
+            ВидИнструкции::ДекодироватьСимвол => {
+                let _ = writeln!(файл, "    pop rcx");
+                let _ = writeln!(файл, "    pop rdx");
+                let _ = writeln!(файл, "    pop rsi");
+                let _ = writeln!(файл, "    sub rdx, rcx");
+                let _ = writeln!(файл, "    add rsi, rcx");
+                let _ = writeln!(файл, "    mov r8, 1");
+                let _ = writeln!(файл, "    movzx eax, byte [rsi]");
+                let _ = writeln!(файл, "    cmp eax, 0x80");
+                let _ = writeln!(файл, "    jb .готово");
+                // Количество байт продолжения, биты значения из первого байта и
+                // наименьшее значение, которое требует столько байт.
+                let _ = writeln!(файл, "    cmp eax, 0xC2");
+                let _ = writeln!(файл, "    jb .замена");
+                let _ = writeln!(файл, "    mov r8, 2");
+                let _ = writeln!(файл, "    and eax, 0x1F");
+                let _ = writeln!(файл, "    mov r10, 0x80");
+                let _ = writeln!(файл, "    cmp byte [rsi], 0xDF");
+                let _ = writeln!(файл, "    jbe .хвост");
+                let _ = writeln!(файл, "    movzx eax, byte [rsi]");
+                let _ = writeln!(файл, "    mov r8, 3");
+                let _ = writeln!(файл, "    and eax, 0x0F");
+                let _ = writeln!(файл, "    mov r10, 0x800");
+                let _ = writeln!(файл, "    cmp byte [rsi], 0xEF");
+                let _ = writeln!(файл, "    jbe .хвост");
+                let _ = writeln!(файл, "    movzx eax, byte [rsi]");
+                let _ = writeln!(файл, "    mov r8, 4");
+                let _ = writeln!(файл, "    and eax, 0x07");
+                let _ = writeln!(файл, "    mov r10, 0x10000");
+                let _ = writeln!(файл, "    cmp byte [rsi], 0xF4");
+                let _ = writeln!(файл, "    ja .замена");
+                let _ = writeln!(файл, ".хвост:");
+                let _ = writeln!(файл, "    cmp r8, rdx");
+                let _ = writeln!(файл, "    ja .замена");
+                let _ = writeln!(файл, "    mov r9, 1");
+                let _ = writeln!(файл, ".продолжение:");
+                let _ = writeln!(файл, "    movzx ebx, byte [rsi+r9]");
+                let _ = writeln!(файл, "    mov edi, ebx");
+                let _ = writeln!(файл, "    and edi, 0xC0");
+                let _ = writeln!(файл, "    cmp edi, 0x80");
+                let _ = writeln!(файл, "    jne .замена");
+                let _ = writeln!(файл, "    shl eax, 6");
+                let _ = writeln!(файл, "    and ebx, 0x3F");
+                let _ = writeln!(файл, "    or eax, ebx");
+                let _ = writeln!(файл, "    inc r9");
+                let _ = writeln!(файл, "    cmp r9, r8");
+                let _ = writeln!(файл, "    jb .продолжение");
+                // Слишком длинные записи, суррогаты и значения за пределами Юникода.
+                let _ = writeln!(файл, "    cmp rax, r10");
+                let _ = writeln!(файл, "    jb .замена");
+                let _ = writeln!(файл, "    cmp eax, 0x10FFFF");
+                let _ = writeln!(файл, "    ja .замена");
+                let _ = writeln!(файл, "    cmp eax, 0xD800");
+                let _ = writeln!(файл, "    jb .готово");
+                let _ = writeln!(файл, "    cmp eax, 0xDFFF");
+                let _ = writeln!(файл, "    ja .готово");
+                let _ = writeln!(файл, ".замена:");
+                let _ = writeln!(файл, "    mov eax, 0xFFFD");
+                let _ = writeln!(файл, "    mov r8, 1");
+                let _ = writeln!(файл, ".готово:");
+                let _ = writeln!(файл, "    add rcx, r8");
+                let _ = writeln!(файл, "    push rax");
+                let _ = writeln!(файл, "    push rcx");
+            }
+            ВидИнструкции::ЗакодироватьСимвол => {
+                let _ = writeln!(файл, "    pop rax");
+                let _ = writeln!(файл, "    pop rcx");
+                let _ = writeln!(файл, "    pop rdx");
+                let _ = writeln!(файл, "    pop rdi");
+                let _ = writeln!(файл, "    cmp rax, 0x10FFFF");
+                let _ = writeln!(файл, "    ja .замена");
+                let _ = writeln!(файл, "    cmp rax, 0xD800");
+                let _ = writeln!(файл, "    jb .длина");
+                let _ = writeln!(файл, "    cmp rax, 0xDFFF");
+                let _ = writeln!(файл, "    ja .длина");
+                let _ = writeln!(файл, ".замена:");
+                let _ = writeln!(файл, "    mov rax, 0xFFFD");
+                let _ = writeln!(файл, ".длина:");
+                let _ = writeln!(файл, "    mov r8, 1");
+                let _ = writeln!(файл, "    cmp rax, 0x80");
+                let _ = writeln!(файл, "    jb .место");
+                let _ = writeln!(файл, "    mov r8, 2");
+                let _ = writeln!(файл, "    cmp rax, 0x800");
+                let _ = writeln!(файл, "    jb .место");
+                let _ = writeln!(файл, "    mov r8, 3");
+                let _ = writeln!(файл, "    cmp rax, 0x10000");
+                let _ = writeln!(файл, "    jb .место");
+                let _ = writeln!(файл, "    mov r8, 4");
+                let _ = writeln!(файл, ".место:");
+                let _ = writeln!(файл, "    cmp rcx, rdx");
+                let _ = writeln!(файл, "    ja .не_помещается");
+                let _ = writeln!(файл, "    sub rdx, rcx");
+                let _ = writeln!(файл, "    cmp r8, rdx");
+                let _ = writeln!(файл, "    ja .не_помещается");
+                let _ = writeln!(файл, "    add rdi, rcx");
+                // Байты продолжения записываются с конца, по 6 бит значения в каждый.
+                let _ = writeln!(файл, "    mov rcx, r8");
+                let _ = writeln!(файл, ".продолжение:");
+                let _ = writeln!(файл, "    dec rcx");
+                let _ = writeln!(файл, "    jz .первый");
+                let _ = writeln!(файл, "    mov rbx, rax");
+                let _ = writeln!(файл, "    and rbx, 0x3F");
+                let _ = writeln!(файл, "    or rbx, 0x80");
+                let _ = writeln!(файл, "    mov [rdi+rcx], bl");
+                let _ = writeln!(файл, "    shr rax, 6");
+                let _ = writeln!(файл, "    jmp .продолжение");
+                // Старшие биты первого байта: 110, 1110 или 11110 по длине.
+                let _ = writeln!(файл, ".первый:");
+                let _ = writeln!(файл, "    cmp r8, 1");
+                let _ = writeln!(файл, "    je .записать_первый");
+                let _ = writeln!(файл, "    mov rbx, 0xFF");
+                let _ = writeln!(файл, "    mov cl, 8");
+                let _ = writeln!(файл, "    sub cl, r8b");
+                let _ = writeln!(файл, "    shl rbx, cl");
+                let _ = writeln!(файл, "    or rax, rbx");
+                let _ = writeln!(файл, ".записать_первый:");
+                let _ = writeln!(файл, "    mov [rdi], al");
+                let _ = writeln!(файл, "    push r8");
+                let _ = writeln!(файл, "    jmp инструкция_{следующая}", следующая = индекс + 1);
+                let _ = writeln!(файл, ".не_помещается:");
+                let _ = writeln!(файл, "    push 0");
+            }
             ВидИнструкции::Возврат => {
                 let _ = writeln!(файл, "    ret");
             }
             ВидИнструкции::ВнутреннийВызов(индекс_инструкции_пп_цели) => {
                 let _ = writeln!(файл, "    call инструкция_{индекс_инструкции_пп_цели}");
             }
-            ВидИнструкции::ВнешнийВызов{индекс, арность, результат} => {
+            ВидИнструкции::ВнешнийВызов{индекс, параметры, результат} => {
+                // System V: целые и указатели раскладываются по общим
+                // регистрам, вещественные по xmm, каждые в своём порядке.
                 let регистры = &["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
-                if *арность > регистры.len() {
-                    сделать!(&инструкция.лок, "Слишком большая арность");
-                    return Err(())
-                }
-                for регистр in &регистры[0..*арность] {
-                    let _ = writeln!(файл, "    pop {регистр}");
+                let регистры_xmm = &["xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7"];
+                let mut занято_регистров = 0;
+                let mut занято_регистров_xmm = 0;
+                for параметр in параметры {
+                    match параметр {
+                        Тип::Вещ32 | Тип::Вещ64 => {
+                            let Some(регистр) = регистры_xmm.get(занято_регистров_xmm) else {
+                                сделать!(&инструкция.лок, "Слишком много вещественных аргументов");
+                                return Err(())
+                            };
+                            let _ = writeln!(файл, "    pop rax");
+                            if *параметр == Тип::Вещ32 {
+                                let _ = writeln!(файл, "    movd {регистр}, eax");
+                            } else {
+                                let _ = writeln!(файл, "    movq {регистр}, rax");
+                            }
+                            занято_регистров_xmm += 1;
+                        }
+                        _ => {
+                            let Some(регистр) = регистры.get(занято_регистров) else {
+                                сделать!(&инструкция.лок, "Слишком большая арность");
+                                return Err(())
+                            };
+                            let _ = writeln!(файл, "    pop {регистр}");
+                            занято_регистров += 1;
+                        }
+                    }
                 }
+                // Процедурам с переменным числом аргументов нужно знать,
+                // сколько xmm регистров занято.
+                let _ = writeln!(файл, "    mov eax, {занято_регистров_xmm}");
                 let _ = writeln!(файл, "    call {имя}", имя = внешние_символы[*индекс].0);
                 if let Some(результат) = результат {
                     match результат {
@@ -482,6 +922,10 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
                             let _ = writeln!(файл, "    movd eax, xmm0");
                             let _ = writeln!(файл, "    push rax");
                         }
+                        Тип::Вещ64 => {
+                            let _ = writeln!(файл, "    movq rax, xmm0");
+                            let _ = writeln!(файл, "    push rax");
+                        }
                         _ => {
                             сделать!(&инструкция.лок, "Кодогенерация возврата типа «{тип}» из внешних процедур",
                                      тип = результат.текст());
@@ -507,6 +951,20 @@ fn сгенерировать_инструкции(файл: &mut impl Write, п
     Ok(())
 }
 
+fn сгенерировать_байты(файл: &mut impl Write, байты: &[u8]) {
+    let длинна_строки = 10;
+    for строка in байты.chunks(длинна_строки) {
+        let _ = write!(файл, "    db");
+        for (столбец, байт) in строка.iter().enumerate() {
+            let _ = write!(файл, " {байт:#04X}");
+            if столбец + 1 < строка.len() {
+                let _ = write!(файл, ",");
+            }
+        }
+        let _ = writeln!(файл);
+    }
+}
+
 pub fn сгенерировать_исполняемый_файл(путь_к_исполняемому: &Path, пп: &ПП, точка_входа_программы: usize) -> Результат<()> {
     let статический = пп.внешние_символы.len() == 0;
 
@@ -536,31 +994,16 @@ pub fn сгенерировать_исполняемый_файл(путь_к_и
     }
 
     let _ = writeln!(&mut файл, "данные:");
-    let длинна_строки = 10;
-    let количество_строк = пп.иниц_данные.len()/10;
-    let остаток_строки = пп.иниц_данные.len()%длинна_строки;
-    for строка in 0..количество_строк {
-        let _ = write!(&mut файл, "    db");
-        for столбец in 0..длинна_строки {
-            let индекс = строка*длинна_строки + столбец;
-            let _ = write!(&mut файл, " {байт:#04X}", байт = пп.иниц_данные[индекс]);
-            if столбец + 1 < длинна_строки {
-                let _ = write!(&mut файл, ",");
-            }
-        }
-        let _ = writeln!(&mut файл);
-    }
-    if остаток_строки > 0 {
-        let _ = write!(&mut файл, "    db");
-        for столбец in 0..остаток_строки {
-            let индекс = количество_строк*длинна_строки + столбец;
-            let _ = write!(&mut файл, " {байт:#04X}", байт = пп.иниц_данные[индекс]);
-            if столбец + 1 < остаток_строки {
-                let _ = write!(&mut файл, ",");
-            }
-        }
-        let _ = writeln!(&mut файл);
+    let mut указатели = пп.указатели_в_иниц_данных.clone();
+    указатели.sort();
+    let mut начало = 0;
+    for смещение in указатели {
+        сгенерировать_байты(&mut файл, &пп.иниц_данные[начало..смещение]);
+        let указатель = u64::from_le_bytes(пп.иниц_данные[смещение..смещение + РАЗМЕР_СЛОВА].try_into().unwrap());
+        let _ = writeln!(&mut файл, "    dq данные+{указатель}");
+        начало = смещение + РАЗМЕР_СЛОВА;
     }
+    сгенерировать_байты(&mut файл, &пп.иниц_данные[начало..]);
     let размер_второго_стека = 1_000_000;
     let _ = writeln!(&mut файл, "    rb {}", пп.размер_неиниц_данных + размер_второго_стека);
     let _ = writeln!(&mut файл, "начало_второго_стека:");
@@ -45,6 +45,61 @@ fn прочитать_содержимое_файла(путь_к_файлу: &P
         })
 }
 
+/// Переменная окружения со списком дополнительных каталогов поиска модулей,
+/// разделённых так же, как в PATH. Имя латиницей, потому что большинство
+/// оболочек не позволяют задавать переменные с кириллицей в имени.
+const ПЕРЕМЕННАЯ_ПУТЕЙ_МОДУЛЕЙ: &str = "HUYA_PATH";
+
+/// Компилирует файл целиком и сообщает сколько всего было ошибок.
+fn скомпилировать_программу(программа: &mut Программа, путь_к_файлу: &Path) -> Результат<()> {
+    // Каталоги из флагов «-путь» уже добавлены и просматриваются первыми.
+    if let Some(пути) = env::var_os(ПЕРЕМЕННАЯ_ПУТЕЙ_МОДУЛЕЙ) {
+        программа.пути_поиска_модулей.extend(env::split_paths(&пути));
+    }
+    let содержимое: Vec<char> = прочитать_содержимое_файла(путь_к_файлу, None)?;
+    let mut лекс = Лексер::новый(путь_к_файлу, &содержимое);
+    let результат = программа.скомпилировать_лексемы(&mut лекс);
+    let количество_ошибок = диагностика::количество_ошибок();
+    if количество_ошибок > 0 {
+        if диагностика::слишком_много_ошибок() {
+            eprintln!("ИНФО: достигнуто максимальное количество ошибок, компиляция прервана досрочно.");
+        }
+        eprintln!("ОШИБКА: компиляция не удалась. Всего {количество_ошибок} {ошибок}.",
+                  ошибок = диагностика::ЧИСУЩ_ОШИБКА.текст(количество_ошибок));
+        return Err(());
+    }
+    результат?;
+    программа.завершить_компиляцию();
+    Ok(())
+}
+
+/// Значение флага «-макс-ошибок». Ноль снимает ограничение.
+fn разобрать_макс_количество_ошибок(флаг: &str, значение: Option<String>) -> Результат<()> {
+    let Some(значение) = значение else {
+        eprintln!("ОШИБКА: Флаг «{флаг}» требует значение.");
+        return Err(())
+    };
+    match значение.parse::<usize>() {
+        Ok(0) => диагностика::установить_макс_количество_ошибок(usize::MAX),
+        Ok(количество) => диагностика::установить_макс_количество_ошибок(количество),
+        Err(_) => {
+            eprintln!("ОШИБКА: Значение флага «{флаг}» должно быть натуральным числом, но повстречалось «{значение}».");
+            return Err(())
+        }
+    }
+    Ok(())
+}
+
+fn разобрать_путь_поиска_модулей(флаг: &str, значение: Option<String>) -> Результат<PathBuf> {
+    match значение {
+        Some(значение) => Ok(PathBuf::from(значение)),
+        None => {
+            eprintln!("ОШИБКА: Флаг «{флаг}» требует значение.");
+            Err(())
+        }
+    }
+}
+
 struct Команда {
     имя: &'static str,
     сигнатура: &'static str,
@@ -59,10 +114,12 @@ struct Команда {
 const КОМАНДЫ: &[Команда] = &[
     Команда {
         имя: "комп",
-        сигнатура: "[-пуск] [-вывод <файл-вывода>] <файл-ввода>",
+        сигнатура: "[-пуск] [-без-проверок] [-макс-ошибок <число>] [-путь <каталог>] [-вывод <файл-вывода>] <файл-ввода>",
         описание: "Скомпилировать файлы исходного кода в исполняемый файл для платформы Linux x86_64.",
         запустить: |программа, mut аргы| {
             let mut пуск = false;
+            let mut без_проверок = false;
+            let mut пути_поиска_модулей = Vec::new();
             let mut файл_ввода = None;
             let mut файл_вывода = None;
 
@@ -70,6 +127,9 @@ const КОМАНДЫ: &[Команда] = &[
                 match аргы.next() {
                     Some(арг) => match арг.as_str() {
                         "-пуск" => пуск = true,
+                        "-без-проверок" => без_проверок = true,
+                        "-макс-ошибок" => разобрать_макс_количество_ошибок(&арг, аргы.next())?,
+                        "-путь" => пути_поиска_модулей.push(разобрать_путь_поиска_модулей(&арг, аргы.next())?),
                         "-вывод" => {
                             match аргы.next() {
                                 Some(арг) => файл_вывода = Some(арг),
@@ -102,10 +162,9 @@ const КОМАНДЫ: &[Команда] = &[
             };
 
             let mut программа = Программа::default();
-            let содержимое: Vec<char> = прочитать_содержимое_файла(&файл_ввода, None)?;
-            let mut лекс = Лексер::новый(&файл_ввода, &содержимое);
-            программа.скомпилировать_лексемы(&mut лекс)?;
-            программа.завершить_компиляцию();
+            программа.пп.без_проверок_границ = без_проверок;
+            программа.пути_поиска_модулей = пути_поиска_модулей;
+            скомпилировать_программу(&mut программа, &файл_ввода)?;
             let процедура_точки_входа = "главная";
             if let Some(процедура) = программа.имена.процедуры.get(процедура_точки_входа) {
                 let точка_входа = match процедура.точка_входа {
@@ -161,16 +220,19 @@ const КОМАНДЫ: &[Команда] = &[
     },
     Команда {
         имя: "интер",
-        сигнатура: "[-отлад] <путь_к_файлу>",
+        сигнатура: "[-отлад] [-макс-ошибок <число>] [-путь <каталог>] <путь_к_файлу>",
         описание: "Интерпретировать Промежуточное Представление скомпилированного файла",
         запустить: |программа, mut аргы| {
             let mut режим_отладки = false;
+            let mut пути_поиска_модулей = Vec::new();
             let mut путь_к_файлу = None;
 
             loop {
                 match аргы.next() {
                     Some(арг) => match арг.as_str() {
                         "-отлад" => режим_отладки = true,
+                        "-макс-ошибок" => разобрать_макс_количество_ошибок(&арг, аргы.next())?,
+                        "-путь" => пути_поиска_модулей.push(разобрать_путь_поиска_модулей(&арг, аргы.next())?),
                         _ => {
                             if путь_к_файлу.is_some() {
                                 пример(программа);
@@ -193,11 +255,9 @@ const КОМАНДЫ: &[Команда] = &[
                 return Err(());
             };
 
-            let содержимое: Vec<char> = прочитать_содержимое_файла(&путь_к_файлу, None)?;
-            let mut лекс = Лексер::новый(&путь_к_файлу, &содержимое);
             let mut программа = Программа::default();
-            программа.скомпилировать_лексемы(&mut лекс)?;
-            программа.завершить_компиляцию();
+            программа.пути_поиска_модулей = пути_поиска_модулей;
+            скомпилировать_программу(&mut программа, &путь_к_файлу)?;
             let процедура_точки_входа = "главная";
             if let Some(процедура) = программа.имена.процедуры.get(процедура_точки_входа) {
                 let точка_входа = match процедура.точка_входа {
@@ -228,11 +288,8 @@ const КОМАНДЫ: &[Команда] = &[
                 eprintln!("ОШИБКА: требуется файл с программой!");
                 return Err(());
             };
-            let содержимое: Vec<char> = прочитать_содержимое_файла(&путь_к_файлу, None)?;
-            let mut лекс = Лексер::новый(&путь_к_файлу, &содержимое);
             let mut программа = Программа::default();
-            программа.скомпилировать_лексемы(&mut лекс)?;
-            программа.завершить_компиляцию();
+            скомпилировать_программу(&mut программа, &путь_к_файлу)?;
             let процедура_точки_входа = "главная";
             if let Some(процедура) = программа.имена.процедуры.get(процедура_точки_входа) {
                 let точка_входа = match процедура.точка_входа {
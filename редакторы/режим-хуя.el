@@ -17,8 +17,9 @@
   (defconst ключевые-слова-хуя
     '("пер" "про" "конст" "пока" "нч" "кц" "для"
       "если" "то" "иначе" "вернуть" "замкнуть" "пропустить"
-      "структ" "союз" "как" "вкл" "внешняя" "библ" "или"
+      "структ" "перечисление" "союз" "как" "вкл" "внешняя" "библ" "или"
       "и" "истина" "ложь" "лбс" "пбс" "ост" "вилка" "когда" "любое"
+      "прервать" "продолжить" "пусто" "скрыт"
       "либо")))
 
 (defun строковый-литерал-хуя (придел)
@@ -54,6 +55,7 @@
 ;; Она почему-то не работает из коробки
 (defconst подсветка-хуя
   `((строковый-литерал-хуя . font-lock-string-face)
+    ("'\\\\?.'" . font-lock-string-face)
     (,(regexp-opt ключевые-слова-хуя 'symbols) . font-lock-keyword-face)))
 
 ;;;###autoload
@@ -18,6 +18,7 @@ test_fasm() {
     ./сборка/хуяк комп -вывод ./сборка/примеры/09-правило110 ./примеры/09-правило110.хуя
     ./сборка/хуяк комп -вывод ./сборка/примеры/10-игра-жизнь ./примеры/10-игра-жизнь.хуя
     ./сборка/хуяк комп -вывод ./сборка/тесты/тест            ./тесты/тест.хуя
+    ./сборка/хуяк комп -вывод ./сборка/тесты/внешние         ./тесты/внешние.хуя
 
     ./сборка/примеры/01-привет               > ./сборка/примеры/01-привет.хуя.вывод.фазм     && diff -u ./примеры/01-привет.хуя.вывод     ./сборка/примеры/01-привет.хуя.вывод.фазм
     ./сборка/примеры/02-цикл                 > ./сборка/примеры/02-цикл.хуя.вывод.фазм       && diff -u ./примеры/02-цикл.хуя.вывод       ./сборка/примеры/02-цикл.хуя.вывод.фазм
@@ -30,6 +31,7 @@ test_fasm() {
     ./сборка/примеры/09-правило110           > ./сборка/примеры/09-правило110.хуя.вывод.фазм && diff -u ./примеры/09-правило110.хуя.вывод ./сборка/примеры/09-правило110.хуя.вывод.фазм
     ./сборка/примеры/10-игра-жизнь           > ./сборка/примеры/10-игра-жизнь.хуя.вывод.фазм && diff -u ./примеры/10-игра-жизнь.хуя.вывод ./сборка/примеры/10-игра-жизнь.хуя.вывод.фазм
     ./сборка/тесты/тест                      > ./сборка/тесты/тест.хуя.вывод.фазм            && diff -u ./тесты/тест.хуя.вывод            ./сборка/тесты/тест.хуя.вывод.фазм
+    ./сборка/тесты/внешние                   > ./сборка/тесты/внешние.хуя.вывод.фазм         && diff -u ./тесты/внешние.хуя.вывод         ./сборка/тесты/внешние.хуя.вывод.фазм
 }
 
 test_inter() {
@@ -46,5 +48,16 @@ test_inter() {
     ./сборка/хуяк интер ./тесты/тест.хуя                      > ./сборка/тесты/тест.хуя.вывод.интер            && diff -u ./тесты/тест.хуя.вывод            ./сборка/тесты/тест.хуя.вывод.интер
 }
 
+test_errors() {
+    mkdir -p ./сборка/тесты/ошибки/
+    # Модули, которые включают тесты ошибок, лежат отдельно, чтобы цикл
+    # ниже не принял их за тесты.
+    for f in ./тесты/ошибки/*.хуя; do
+        ! HUYA_PATH=./тесты/модули ./сборка/хуяк интер "$f" > "./сборка/$f.вывод.интер" 2>&1
+        diff -u "$f.вывод" "./сборка/$f.вывод.интер"
+    done
+}
+
 test_fasm
 test_inter
+test_errors